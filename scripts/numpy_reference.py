@@ -0,0 +1,61 @@
+#!/usr/bin/env python3
+"""Decode random bit patterns with numpy's .view() and print them as JSON.
+
+Usage: numpy_reference.py --format=fp16|fp32 [--count=N] [--seed=S]
+
+Each sample carries the pattern in hex and either the exact value as a
+numerator/denominator pair (from float.as_integer_ratio, exact because both
+formats widen to float64 losslessly) or a special tag. Exits with status 3
+when numpy is not installed so callers can skip cleanly.
+"""
+
+import json
+import math
+import random
+import sys
+
+UNSIGNED = {"fp16": ("uint16", "float16", 16), "fp32": ("uint32", "float32", 32)}
+
+
+def parse_args(argv):
+    args = {"format": "fp16", "count": 4096, "seed": 1}
+    for arg in argv:
+        key, _, value = arg.removeprefix("--").partition("=")
+        if key not in args:
+            sys.exit(f"unknown argument: {arg}")
+        args[key] = value if key == "format" else int(value)
+    if args["format"] not in UNSIGNED:
+        sys.exit(f"unsupported format: {args['format']}")
+    return args
+
+
+def main():
+    args = parse_args(sys.argv[1:])
+    try:
+        import numpy as np
+    except ImportError:
+        print("numpy unavailable", file=sys.stderr)
+        sys.exit(3)
+
+    unsigned, floating, width = UNSIGNED[args["format"]]
+    rng = random.Random(args["seed"])
+    patterns = [rng.getrandbits(width) for _ in range(args["count"])]
+    values = np.array(patterns, dtype=unsigned).view(floating)
+
+    samples = []
+    for pattern, value in zip(patterns, values):
+        value = float(value)
+        sample = {"hex": f"{pattern:0{width // 4}X}", "sign": math.copysign(1.0, value) < 0}
+        if math.isnan(value):
+            sample["special"] = "nan"
+        elif math.isinf(value):
+            sample["special"] = "inf"
+        else:
+            num, den = value.as_integer_ratio()
+            sample["num"], sample["den"] = str(num), str(den)
+        samples.append(sample)
+    json.dump({"format": args["format"], "samples": samples}, sys.stdout)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,231 @@
+use super::*;
+
+#[test]
+fn native_hex_defaults_to_fp64() {
+	let cli = Cli::try_parse_from(["afcvt", "--native-hex", "000000000000F03F"]).unwrap();
+	assert!(matches!(cli.format, FormatChoice::Fp64));
+	let cli =
+		Cli::try_parse_from(["afcvt", "--format", "fp32", "--native-hex", "0000C03F"]).unwrap();
+	assert!(matches!(cli.format, FormatChoice::Fp32));
+}
+
+#[test]
+fn trap_fails_only_for_listed_exceptions() {
+	let cli = Cli::try_parse_from(["afcvt", "--trap", "inexact,overflow", "0.1"]).unwrap();
+	assert_eq!(cli.trap, vec![Exception::Inexact, Exception::Overflow]);
+	assert!(check_traps(&[Exception::Inexact], &cli.trap).is_err());
+	assert!(check_traps(&[], &cli.trap).is_ok());
+	assert!(check_traps(&[Exception::Underflow], &cli.trap).is_ok());
+}
+
+#[test]
+fn reformat_requires_an_encoded_input() {
+	assert!(Cli::try_parse_from(["afcvt", "--reformat", "--hex", "3FC00000"]).is_ok());
+	assert!(Cli::try_parse_from(["afcvt", "--reformat", "--bits", "0"]).is_ok());
+	assert!(Cli::try_parse_from(["afcvt", "--reformat", "1.5"]).is_err());
+}
+
+#[test]
+fn value_file_supplies_a_long_decimal() {
+	let digits = format!("0.{}", "1".repeat(200));
+	let path = std::env::temp_dir().join(format!("afcvt-value-file-{}.txt", std::process::id()));
+	fs::write(&path, format!("{digits}\n")).unwrap();
+	let cli = Cli::try_parse_from(["afcvt", "--format", "fp64", "--value-file", path.to_str().unwrap()]).unwrap();
+	let raw = read_value_file(cli.value_file.as_deref().unwrap()).unwrap();
+	fs::remove_file(&path).unwrap();
+	assert_eq!(raw, digits);
+
+	let spec = resolve_format(&cli).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal(&raw).unwrap(), &spec, RoundingMode::HalfEven);
+	// 0.111… rounds to the same double as 1/9.
+	let ninth = parsed_to_softfloat(
+		&ParsedValue::Finite(BigRational::new(BigInt::from(1), BigInt::from(9))),
+		&spec,
+		RoundingMode::HalfEven,
+	);
+	assert_eq!(softfloat_to_bits(&soft, &spec).unwrap(), softfloat_to_bits(&ninth, &spec).unwrap());
+}
+
+#[test]
+fn json_pretty_spreads_the_report_over_lines() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let report = convert("1.5", &spec, RoundingMode::HalfEven, 32, Notation::Plain).unwrap();
+	let compact = render_json(&report, false).unwrap();
+	let pretty = render_json(&report, true).unwrap();
+	assert!(!compact.contains('\n'));
+	assert!(pretty.contains('\n'));
+	let reparsed: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+	assert_eq!(reparsed, serde_json::from_str::<serde_json::Value>(&compact).unwrap());
+}
+
+#[test]
+fn bash_completions_list_the_format_flag() {
+	let script = |shell| {
+		let mut out = Vec::new();
+		clap_complete::generate(shell, &mut Cli::command(), "afcvt", &mut out);
+		String::from_utf8(out).unwrap()
+	};
+	let bash = script(clap_complete::Shell::Bash);
+	assert!(bash.contains("complete -F _afcvt"));
+	assert!(bash.contains("--format"));
+	assert!(bash.contains("fp8-e4m3"));
+	assert!(script(clap_complete::Shell::Zsh).starts_with("#compdef afcvt"));
+	assert!(script(clap_complete::Shell::Fish).contains("complete -c afcvt"));
+}
+
+#[test]
+fn manpage_is_roff_naming_the_program() {
+	let mut out = Vec::new();
+	clap_mangen::Man::new(Cli::command()).render(&mut out).unwrap();
+	let page = String::from_utf8(out).unwrap();
+	assert!(page.contains(".TH afcvt 1"));
+	assert!(page.contains(".SH NAME\nafcvt \\- "));
+	assert!(page.contains("\\-\\-format"));
+}
+
+#[test]
+fn text_report_aligns_every_colon() {
+	let mut text = TextReport::new(12);
+	text.row("Format", "FP32");
+	text.row("Mantissa (dec)", 4194304);
+	text.row("Exactly halfway", "no");
+	text.row("Rounding carry", "no");
+	text.row("Cont. frac", "[1; 2]");
+	text.row("Roundings", "");
+	text.detail("  half-even    0x3FC00000 error 0".to_string());
+	text.row("Native", "3FC00000 (agrees)");
+	let rendered = text.to_string();
+	let columns: Vec<usize> = rendered
+		.lines()
+		.filter(|line| !line.starts_with(' '))
+		.map(|line| line.find(':').unwrap())
+		.collect();
+	assert_eq!(columns.len(), 7);
+	assert!(columns.iter().all(|&c| c == "Exactly halfway".len()));
+	assert!(rendered.contains("\n  half-even    0x3FC00000 error 0\n"));
+
+	let mut wide = TextReport::new(20);
+	wide.row("Format", "FP32");
+	assert_eq!(wide.to_string(), format!("{:<20}: FP32\n", "Format"));
+}
+
+#[test]
+fn digits_after_point_fixes_the_fractional_digits() {
+	let cli = Cli::try_parse_from(["afcvt", "--digits-after-point", "2", "1234.5678"]).unwrap();
+	let display = display_options(&cli).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1234.5678").unwrap(), &spec, RoundingMode::HalfEven);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(format_rational(&stored, &display), "1234.56");
+	let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+	assert_eq!(format_rational(&half, &display), "0.50");
+
+	assert!(Cli::try_parse_from(["afcvt", "--digits-after-point", "2", "--precision", "4", "1.5"]).is_err());
+	let cli = Cli::try_parse_from(["afcvt", "--digits-after-point", "2", "--notation", "scientific", "1.5"]).unwrap();
+	assert!(display_options(&cli).is_err());
+}
+
+#[test]
+fn sort_orders_batch_rows_by_value() {
+	let cli = Cli::try_parse_from(["afcvt", "--sort", "asc", "3", "1", "2"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	let inputs: Vec<&str> = batch_rows(&cli, &spec, &display).unwrap().iter().map(|row| row.1).collect();
+	assert_eq!(inputs, ["1", "2", "3"]);
+
+	let cli = Cli::try_parse_from(["afcvt", "--sort", "asc", "--", "nan", "0", "-inf", "1"]).unwrap();
+	let rows = batch_rows(&cli, &spec, &display).unwrap();
+	let order: Vec<usize> = rows.iter().map(|row| row.0).collect();
+	assert_eq!(order, [2, 1, 3, 0]);
+
+	let cli = Cli::try_parse_from(["afcvt", "--sort", "desc", "3", "1", "2"]).unwrap();
+	let inputs: Vec<&str> = batch_rows(&cli, &spec, &display).unwrap().iter().map(|row| row.1).collect();
+	assert_eq!(inputs, ["3", "2", "1"]);
+}
+
+#[test]
+fn dedup_counts_inputs_sharing_a_representable() {
+	let cli = Cli::try_parse_from(["afcvt", "--format", "bfloat16", "--dedup", "0.1", "0.10000001", "0.5"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	let groups = dedup_rows(batch_rows(&cli, &spec, &display).unwrap(), &spec).unwrap();
+	assert_eq!(groups.len(), 2);
+	assert_eq!(groups[0].1, ["0.1", "0.10000001"]);
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&groups[0].0, &spec).unwrap()), "3DCD");
+	assert_eq!(groups[1].1, ["0.5"]);
+}
+
+#[test]
+fn several_positionals_convert_each() {
+	let cli = Cli::try_parse_from(["afcvt", "1.0", "2.0", "3.0"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	let rows = batch_rows(&cli, &spec, &display).unwrap();
+	let hex: Vec<String> = rows.iter().map(|row| bits_to_hex(&softfloat_to_bits(&row.2, &spec).unwrap())).collect();
+	assert_eq!(hex, ["3F800000", "40000000", "40400000"]);
+
+	let cli = Cli::try_parse_from(["afcvt", "--hex", "3F800000", "1.0", "2.0"]).unwrap();
+	assert!(batch_rows(&cli, &spec, &display).is_err());
+}
+
+#[test]
+fn summary_footer_follows_the_batch_rows() {
+	let cli = Cli::try_parse_from(["afcvt", "--format", "fp16", "--summary", "0.5", "0.1", "1e10"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	let rows = batch_rows(&cli, &spec, &display).unwrap();
+	let footer = batch_summary(&rows, &spec, &display).unwrap().to_string();
+	assert!(footer.contains("Values      : 3\n"));
+	assert!(footer.contains("Inexact     : 2\n"));
+	assert!(footer.contains("Overflow    : 1\n"));
+	assert!(footer.contains("Max error   : 0.4 ULP\n"));
+}
+
+#[test]
+fn interpret_decides_how_a_positional_is_read() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let decoded = |args: &[&str]| {
+		let cli = Cli::try_parse_from(args).unwrap();
+		match positional_input(cli.values[0].clone(), &cli, &spec).unwrap() {
+			Input::Hex(hex, _) => {
+				let soft = bits_to_softfloat(&hex_to_bits(&hex, 32).unwrap(), &spec).unwrap();
+				Some(softfloat_to_rational(&soft, &spec).unwrap())
+			}
+			_ => None,
+		}
+	};
+	let one_and_half = BigRational::new(BigInt::from(3), BigInt::from(2));
+	assert_eq!(decoded(&["afcvt", "--interpret", "hex", "3FC00000"]), Some(one_and_half.clone()));
+	assert_eq!(decoded(&["afcvt", "--interpret", "auto", "3FC00000"]), Some(one_and_half));
+	assert_eq!(decoded(&["afcvt", "--interpret", "auto", "1.5"]), None);
+	assert_eq!(decoded(&["afcvt", "--interpret", "auto", "3FC000"]), None);
+	assert_eq!(decoded(&["afcvt", "3FC00000"]), None);
+}
+
+#[test]
+fn selftest_passes_for_fp16() {
+	let cli = Cli::try_parse_from(["afcvt", "selftest", "fp16", "--samples", "500"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	run_command(cli.command.as_ref().unwrap(), &spec, cli.rounding, &display, false).unwrap();
+
+	let report = self_test(&preset_spec(FormatChoice::Fp16).unwrap(), 500, 1).unwrap();
+	assert!(report.checks > 1000);
+	assert!(report.failures.is_empty(), "{:?}", report.failures);
+}
+
+#[test]
+fn selftest_rejects_custom() {
+	let cli = Cli::try_parse_from(["afcvt", "selftest", "custom"]).unwrap();
+	let spec = resolve_format(&cli).unwrap();
+	let display = display_options(&cli).unwrap();
+	assert!(run_command(cli.command.as_ref().unwrap(), &spec, cli.rounding, &display, false).is_err());
+}
+
+#[test]
+fn summary_rejects_single_value_inputs() {
+	for flag in ["--value-file", "--hex", "--bits", "--decode-binary"] {
+		let parsed = Cli::try_parse_from(["afcvt", "--format", "fp16", "--summary", flag, "1"]);
+		assert!(parsed.is_err(), "{flag}");
+	}
+}
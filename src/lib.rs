@@ -0,0 +1,3487 @@
+//! Conversion between decimal values and arbitrary IEEE 754-style binary
+//! formats. The `afcvt` binary is a thin CLI over this crate.
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`; `std` adds the clap derives the CLI parses its options with.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::{Context, Result, anyhow, bail};
+use bigdecimal::BigDecimal;
+#[cfg(feature = "std")]
+use clap::ValueEnum;
+use core::cmp::Ordering;
+use core::str::FromStr;
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{Float, One, Signed, ToPrimitive, Zero};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Copy, Clone, Debug)]
+pub enum QuotientRounding {
+    NearestEven,
+    TowardZero,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum FormatChoice {
+    Fp16,
+    Bfloat16,
+    Fp32,
+    Fp64,
+    Tf32,
+    /// AMD FP24 shader format (7 exponent, 16 significand bits)
+    Fp24,
+    /// OCP FP8 E4M3 (finite-only, no infinities)
+    #[cfg_attr(feature = "std", value(name = "fp8-e4m3"))]
+    Fp8E4m3,
+    /// OCP FP8 E5M2
+    #[cfg_attr(feature = "std", value(name = "fp8-e5m2"))]
+    Fp8E5m2,
+    /// x87 80-bit extended precision (explicit integer bit)
+    Fp80,
+    Custom,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum RoundingMode {
+    #[cfg_attr(feature = "std", value(alias = "nearest", alias = "even"))]
+    HalfEven,
+    #[cfg_attr(feature = "std", value(alias = "trunc", alias = "zero"))]
+    TowardZero,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum NativeFloat {
+    F32,
+    F64,
+}
+
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum Notation {
+    Plain,
+    Scientific,
+}
+
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    pub precision: usize,
+    pub notation: Notation,
+    pub trailing_zeros: bool,
+    pub decimal_separator: char,
+    pub hex_case: HexCase,
+    /// Print `+` before positive values (and zero) so columns line up with negatives.
+    pub plus_sign: bool,
+}
+
+/// Letter case for printed hex digits. `bits_to_hex` always produces upper
+/// case; renderers pass the result through `HexCase::apply`.
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum HexCase {
+    #[default]
+    Upper,
+    Lower,
+}
+
+impl HexCase {
+    pub fn apply(self, hex: &str) -> String {
+        match self {
+            HexCase::Upper => hex.to_string(),
+            HexCase::Lower => hex.to_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FloatSpec {
+    pub name: &'static str,
+    pub exponent_bits: usize,
+    pub significand_bits: usize,
+    /// Whether the all-ones exponent with a zero fraction is an infinity.
+    /// Infinities reserve that exponent, so they need `AllOnesExponent` NaNs.
+    pub has_infinity: bool,
+    /// Which encodings are NaN; see `with_specials`.
+    pub nan_encoding: NanPolicy,
+    /// Subtracted from the exponent field to get the unbiased exponent;
+    /// `standard_bias` unless overridden with `with_bias`.
+    pub bias: i32,
+    /// The leading significand bit is stored after the exponent, as in x87
+    /// extended precision, instead of implied by it. `significand_bits`
+    /// still counts only the fraction below it.
+    pub explicit_integer_bit: bool,
+}
+
+/// Which bit patterns of a format are NaN.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum NanPolicy {
+    /// Every all-ones-exponent pattern that is not an infinity (IEEE 754)
+    AllOnesExponent,
+    /// Only the all-ones exponent and fraction; the rest of that exponent
+    /// holds normal values (the "FN" encoding of FP8 E4M3)
+    AllOnesPattern,
+    /// No NaN at all: the all-ones exponent is an ordinary binade
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Class {
+    Normal,
+    Subnormal,
+    Zero,
+    PosInfinity,
+    NegInfinity,
+    Nan,
+    /// Explicit-integer-bit encodings that modern hardware rejects: an integer
+    /// bit of 1 under a zero exponent field, 0 under a normal one, and 0 under
+    /// the all-ones field (with a zero or nonzero fraction).
+    PseudoDenormal,
+    Unnormal,
+    PseudoInfinity,
+    PseudoNan,
+}
+
+impl Class {
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, Class::PosInfinity | Class::NegInfinity)
+    }
+
+    pub fn is_pseudo(&self) -> bool {
+        matches!(
+            self,
+            Class::PseudoDenormal | Class::Unnormal | Class::PseudoInfinity | Class::PseudoNan
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SoftFloat {
+    pub class: Class,
+    pub sign: bool,
+    pub exponent: i32, // unbiased exponent for Normal/Subnormal; min exp for zero
+    pub significand: BigUint, // stored fraction bits (no implicit leading 1)
+}
+
+/// Position of a value on the integer line of encodings: the exponent and
+/// fraction fields read as one magnitude, negated for negative signs, so that
+/// adjacent representables differ by one and both zeros map to 0. An explicit
+/// integer bit is left out, so pseudo encodings have no position.
+fn softfloat_ordinal(sf: &SoftFloat, spec: &FloatSpec) -> Result<Option<BigInt>> {
+    if sf.class == Class::Nan {
+        return Ok(None);
+    }
+    if sf.class.is_pseudo() {
+        bail!("{:?} encodings have no ordinal position", sf.class);
+    }
+    let mut bits = softfloat_to_bits(sf, spec)?;
+    if spec.explicit_integer_bit {
+        bits.remove(1 + spec.exponent_bits);
+    }
+    let magnitude = BigUint::parse_bytes(&bits.as_bytes()[1..], 2).unwrap_or_else(BigUint::zero);
+    let ordinal = BigInt::from(magnitude);
+    Ok(Some(if sf.sign { -ordinal } else { ordinal }))
+}
+
+/// Inverse of `softfloat_ordinal`: the encoding at a signed position on the
+/// ordinal line.
+fn ordinal_to_softfloat(ordinal: &BigInt, spec: &FloatSpec) -> Result<SoftFloat> {
+    let width = total_bits(spec)? - 1 - usize::from(spec.explicit_integer_bit);
+    let magnitude = ordinal.magnitude().to_str_radix(2);
+    if magnitude.len() > width {
+        bail!(
+            "ordinal {ordinal} is outside the {} encoding space",
+            spec.name
+        );
+    }
+    let sign = if ordinal.is_negative() { '1' } else { '0' };
+    let mut bits = format!("{sign}{magnitude:0>width$}");
+    if spec.explicit_integer_bit {
+        let exponent_field = &bits[1..1 + spec.exponent_bits];
+        let integer_bit = if exponent_field.contains('1') {
+            '1'
+        } else {
+            '0'
+        };
+        bits.insert(1 + spec.exponent_bits, integer_bit);
+    }
+    bits_to_softfloat(&bits, spec)
+}
+
+/// Properties `nearest_with` can search for.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum Property {
+    /// Stored fraction field is even
+    EvenMantissa,
+    /// Magnitude is an exact power of two
+    PowerOfTwo,
+    /// Value is an integer
+    Integer,
+}
+
+fn has_property(sf: &SoftFloat, value: &BigRational, property: Property) -> bool {
+    match property {
+        Property::EvenMantissa => (&sf.significand % 2u32).is_zero(),
+        Property::PowerOfTwo => {
+            let abs = value.abs();
+            !abs.is_zero() && abs == pow2(log2_floor(&abs))
+        }
+        Property::Integer => value.is_integer(),
+    }
+}
+
+/// The finite representable nearest to `value` that has `property`, or
+/// `None` if there is none. Candidates are the rounded value and its two
+/// neighbours on the ordinal line, plus the property's own nearest points
+/// (floor/ceil, the bracketing powers of two) when they are representable.
+/// Past the overflow threshold the powers of two come from the top binade.
+/// Ties go to the larger magnitude.
+pub fn nearest_with(
+    value: &BigRational,
+    spec: &FloatSpec,
+    property: Property,
+) -> Result<Option<SoftFloat>> {
+    let quantize = |v: &BigRational| {
+        parsed_to_softfloat(
+            &ParsedValue::Finite(v.clone()),
+            spec,
+            RoundingMode::HalfEven,
+        )
+    };
+    let mut candidates = Vec::new();
+    if let Some(ordinal) = softfloat_ordinal(&quantize(value), spec)? {
+        for delta in -1..=1 {
+            if let Ok(sf) = ordinal_to_softfloat(&(&ordinal + delta), spec) {
+                candidates.push(sf);
+            }
+        }
+    }
+
+    let mut exact = vec![value.floor(), value.ceil()];
+    if value.is_zero() {
+        exact.push(pow2(min_exponent(spec) - spec.significand_bits as i32));
+    } else {
+        let exp = log2_floor(&value.abs()).min(log2_floor(&max_finite(spec)));
+        for p in [pow2(exp), pow2(exp + 1)] {
+            exact.push(if value.is_negative() { -p } else { p });
+        }
+    }
+    for v in exact {
+        let sf = quantize(&v);
+        if softfloat_to_rational(&sf, spec) == Some(v) {
+            candidates.push(sf);
+        }
+    }
+
+    let mut best: Option<(BigRational, BigRational, SoftFloat)> = None;
+    for sf in candidates {
+        let Some(v) = softfloat_to_rational(&sf, spec) else {
+            continue;
+        };
+        if !has_property(&sf, &v, property) {
+            continue;
+        }
+        let distance = (&v - value).abs();
+        let better = match &best {
+            None => true,
+            Some((d, magnitude, _)) => distance < *d || (distance == *d && v.abs() > *magnitude),
+        };
+        if better {
+            best = Some((distance, v.abs(), sf));
+        }
+    }
+    Ok(best.map(|(_, _, sf)| sf))
+}
+
+/// The exact midpoints between `sf` and its lower and upper neighbours on the
+/// ordinal line: the boundaries where round-to-nearest switches to a
+/// neighbour. Beyond the largest finite value the boundary is the overflow
+/// threshold, half a gap past it. `None` for infinities and NaN.
+pub fn rounding_midpoints(
+    sf: &SoftFloat,
+    spec: &FloatSpec,
+) -> Result<Option<(BigRational, BigRational)>> {
+    let (Some(value), Some(ordinal)) = (
+        softfloat_to_rational(sf, spec),
+        softfloat_ordinal(sf, spec)?,
+    ) else {
+        return Ok(None);
+    };
+    let half_gap = ulp_gap(&value.abs(), spec) / BigInt::from(2);
+    let midpoint = |delta: i32, fallback: BigRational| {
+        ordinal_to_softfloat(&(&ordinal + delta), spec)
+            .ok()
+            .and_then(|neighbour| softfloat_to_rational(&neighbour, spec))
+            .map(|neighbour| (&value + neighbour) / BigInt::from(2))
+            .unwrap_or(fallback)
+    };
+    Ok(Some((
+        midpoint(-1, &value - &half_gap),
+        midpoint(1, &value + &half_gap),
+    )))
+}
+
+/// The set of exact inputs that quantize to one encoding: an interval whose
+/// ends are `None` when unbounded (a saturating format's extremes) and
+/// closed when the end itself rounds to the encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Preimage {
+    pub lo: Option<BigRational>,
+    pub lo_closed: bool,
+    pub hi: Option<BigRational>,
+    pub hi_closed: bool,
+}
+
+impl Preimage {
+    /// The first and last decimals with exactly `digits` places after the
+    /// point inside the interval, scaled by `10^digits`; `None` when no such
+    /// decimal fits or an end is unbounded.
+    pub fn decimals_with_digits(&self, digits: u32) -> Option<(BigInt, BigInt)> {
+        let scale = BigRational::from_integer(BigInt::from(10u32).pow(digits));
+        let lo = self.lo.as_ref()? * &scale;
+        let hi = self.hi.as_ref()? * &scale;
+        let mut first = lo.ceil().to_integer();
+        if !self.lo_closed && BigRational::from_integer(first.clone()) == lo {
+            first += 1;
+        }
+        let mut last = hi.floor().to_integer();
+        if !self.hi_closed && BigRational::from_integer(last.clone()) == hi {
+            last -= 1;
+        }
+        (first <= last).then_some((first, last))
+    }
+}
+
+/// Every exact input that `rounding` quantizes to `sf`, bounded by the
+/// midpoints to the neighbours (or by the neighbours themselves for
+/// toward-zero). Each zero keeps its own sign's side. `None` for infinities
+/// and NaN.
+pub fn preimage(
+    sf: &SoftFloat,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> Result<Option<Preimage>> {
+    let (Some(value), Some(ordinal)) = (
+        softfloat_to_rational(sf, spec),
+        softfloat_ordinal(sf, spec)?,
+    ) else {
+        return Ok(None);
+    };
+    let gap = ulp_gap(&value.abs(), spec);
+    let neighbour = |delta: i32| {
+        ordinal_to_softfloat(&(&ordinal + delta), spec)
+            .ok()
+            .and_then(|neighbour| softfloat_to_rational(&neighbour, spec))
+    };
+    // Past the largest finite value the bound is the overflow threshold, or
+    // nothing at all when the format saturates instead of overflowing.
+    let beyond = |delta: i32, reach: BigRational| {
+        let saturates = softfloat_to_rational(&overflow_result(delta < 0, spec), spec).is_some();
+        (!saturates).then(|| &value + reach * BigInt::from(delta))
+    };
+    let bound = |delta: i32| match rounding {
+        RoundingMode::HalfEven => match neighbour(delta) {
+            Some(n) => Some((&value + n) / BigInt::from(2)),
+            None => beyond(delta, &gap / BigInt::from(2)),
+        },
+        RoundingMode::TowardZero => match neighbour(delta) {
+            Some(n) => Some(n),
+            None => beyond(delta, gap.clone()),
+        },
+    };
+    let toward_zero = matches!(rounding, RoundingMode::TowardZero);
+    let (lo, hi) = if value.is_zero() {
+        let reach = if toward_zero {
+            gap.clone()
+        } else {
+            &gap / BigInt::from(2)
+        };
+        if sf.sign {
+            (Some(-reach), Some(BigRational::zero()))
+        } else {
+            (Some(BigRational::zero()), Some(reach))
+        }
+    } else if toward_zero && value.is_positive() {
+        (Some(value.clone()), bound(1))
+    } else if toward_zero {
+        (bound(-1), Some(value.clone()))
+    } else {
+        (bound(-1), bound(1))
+    };
+    let target = softfloat_to_bits(sf, spec)?;
+    let lands = |end: &Option<BigRational>| -> Result<bool> {
+        Ok(match end {
+            Some(end) => {
+                let rounded =
+                    parsed_to_softfloat(&ParsedValue::Finite(end.clone()), spec, rounding);
+                softfloat_to_bits(&rounded, spec)? == target
+            }
+            None => false,
+        })
+    };
+    Ok(Some(Preimage {
+        lo_closed: lands(&lo)?,
+        hi_closed: lands(&hi)?,
+        lo,
+        hi,
+    }))
+}
+
+/// The representables within `radius` ordinal steps of `center`, in
+/// increasing order and paired with their offset, `2 * radius + 1` of them
+/// unless the grid runs off the encoding space or into NaN. Infinities are
+/// kept as the last step past the largest finite value. `None` for NaN.
+pub fn ulp_grid(
+    center: &SoftFloat,
+    radius: u32,
+    spec: &FloatSpec,
+) -> Result<Option<Vec<(i64, SoftFloat)>>> {
+    let Some(ordinal) = softfloat_ordinal(center, spec)? else {
+        return Ok(None);
+    };
+    let radius = i64::from(radius);
+    Ok(Some(
+        (-radius..=radius)
+            .filter_map(|offset| {
+                let sf = ordinal_to_softfloat(&(&ordinal + offset), spec).ok()?;
+                (sf.class != Class::Nan).then_some((offset, sf))
+            })
+            .collect(),
+    ))
+}
+
+/// Sort key for IEEE 754 totalOrder: -NaN < -Inf < … < -0 < +0 < … < +Inf <
+/// +NaN, with NaNs further ordered by payload. Read straight off the
+/// encoding, so it is defined for every pattern.
+pub fn total_order_key(sf: &SoftFloat, spec: &FloatSpec) -> Result<BigInt> {
+    let bits = softfloat_to_bits(sf, spec)?;
+    let magnitude =
+        BigInt::from(BigUint::parse_bytes(&bits.as_bytes()[1..], 2).unwrap_or_else(BigUint::zero));
+    Ok(if sf.sign { -magnitude - 1 } else { magnitude })
+}
+
+/// The encoding as an unsigned radix-sort key: every bit flipped when the sign
+/// is set, only the sign bit set otherwise. Unsigned comparison of the keys
+/// then matches `total_order_key`.
+pub fn monotone_key(sf: &SoftFloat, spec: &FloatSpec) -> Result<String> {
+    let bits = softfloat_to_bits(sf, spec)?;
+    Ok(if sf.sign {
+        bits.chars()
+            .map(|c| if c == '1' { '0' } else { '1' })
+            .collect()
+    } else {
+        format!("1{}", &bits[1..])
+    })
+}
+
+/// Signed distance in ordinals from 1.0 to `sf`: positive above one, negative
+/// below. `None` for NaN, or when the format cannot represent 1.0 exactly
+/// (possible with a custom bias).
+pub fn ulps_from_one(sf: &SoftFloat, spec: &FloatSpec) -> Result<Option<BigInt>> {
+    let one = parsed_to_softfloat(
+        &ParsedValue::Finite(BigRational::one()),
+        spec,
+        RoundingMode::HalfEven,
+    );
+    if softfloat_to_rational(&one, spec) != Some(BigRational::one()) {
+        return Ok(None);
+    }
+    let one = softfloat_ordinal(&one, spec)?.expect("1.0 is not NaN");
+    Ok(softfloat_ordinal(sf, spec)?.map(|ordinal| ordinal - one))
+}
+
+/// Models flush-to-zero hardware: subnormals become a zero of the same sign.
+fn flush_subnormal(sf: &SoftFloat) -> SoftFloat {
+    match sf.class {
+        Class::Subnormal => SoftFloat {
+            class: Class::Zero,
+            significand: BigUint::zero(),
+            ..sf.clone()
+        },
+        _ => sf.clone(),
+    }
+}
+
+/// Numeric equality (NaN never equal, ±0 equal) and the ULP distance between
+/// two values, optionally flushing subnormals first.
+pub fn compare_softfloats(
+    actual: &SoftFloat,
+    expected: &SoftFloat,
+    spec: &FloatSpec,
+    flush_subnormals: bool,
+) -> Result<(bool, Option<BigUint>)> {
+    let (actual, expected) = if flush_subnormals {
+        (flush_subnormal(actual), flush_subnormal(expected))
+    } else {
+        (actual.clone(), expected.clone())
+    };
+    let ulps = match (
+        softfloat_ordinal(&actual, spec)?,
+        softfloat_ordinal(&expected, spec)?,
+    ) {
+        (Some(a), Some(b)) => Some((a - b).magnitude().clone()),
+        _ => None,
+    };
+    let equal = ulps.as_ref().is_some_and(|u| u.is_zero());
+    Ok((equal, ulps))
+}
+
+/// What changes when a value moves from one format to another.
+#[derive(Debug, Clone)]
+pub struct FormatDiff {
+    pub exponent_bits_delta: i64,
+    pub significand_bits_delta: i64,
+    pub inexact: bool,
+}
+
+/// Re-rounds `sf` into `to` and reports the field-width changes and whether
+/// the value survived exactly. NaN stays NaN and counts as exact.
+pub fn diff_formats(
+    sf: &SoftFloat,
+    from: &FloatSpec,
+    to: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, FormatDiff) {
+    let converted = parsed_to_softfloat(&softfloat_to_parsed(sf, from), to, rounding);
+    let inexact = match sf.class {
+        Class::Nan => false,
+        _ => {
+            converted.class != sf.class && converted.class.is_infinite()
+                || softfloat_to_rational(&converted, to) != softfloat_to_rational(sf, from)
+        }
+    };
+    let diff = FormatDiff {
+        exponent_bits_delta: to.exponent_bits as i64 - from.exponent_bits as i64,
+        significand_bits_delta: to.significand_bits as i64 - from.significand_bits as i64,
+        inexact,
+    };
+    (converted, diff)
+}
+
+/// Round `value` straight into `narrow`, and separately into `wide` and then
+/// `narrow`, as hardware that derives bfloat16 from an FP32 result does. The
+/// two differ only when the first rounding lands exactly on a tie of the second.
+pub fn double_rounding(
+    value: &ParsedValue,
+    wide: &FloatSpec,
+    narrow: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, SoftFloat) {
+    let direct = parsed_to_softfloat(value, narrow, rounding);
+    let intermediate = parsed_to_softfloat(value, wide, rounding);
+    let (via, _) = diff_formats(&intermediate, wide, narrow, rounding);
+    (direct, via)
+}
+
+pub fn describe_bit_delta(delta: i64, what: &str) -> String {
+    match delta.cmp(&0) {
+        Ordering::Less => format!("{} lost, less {what}", -delta),
+        Ordering::Greater => format!("{delta} gained, more {what}"),
+        Ordering::Equal => format!("{what} unchanged"),
+    }
+}
+
+/// Decimal operand of a subcommand, quantized to the format.
+pub fn decimal_operand(
+    raw: &str,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+    display: &DisplayOptions,
+) -> Result<SoftFloat> {
+    let soft = parsed_to_softfloat(
+        &parse_decimal_with(raw, display.decimal_separator)?,
+        spec,
+        rounding,
+    );
+    // The rational value of "-0" is plain zero; keep the sign the user wrote.
+    let negative = raw.trim_start().starts_with('-');
+    Ok(with_zero_sign(soft, negative))
+}
+
+/// `sf` with its sign replaced by `sign` if it is a zero; other values pass
+/// through unchanged.
+fn with_zero_sign(mut sf: SoftFloat, sign: bool) -> SoftFloat {
+    if sf.class == Class::Zero {
+        sf.sign = sign;
+    }
+    sf
+}
+
+/// Collapses -0 to +0, for callers that do not want signed zeros.
+pub fn normalize_minus_zero(sf: SoftFloat) -> SoftFloat {
+    with_zero_sign(sf, false)
+}
+
+/// Rounded product with the exact extended-real product it came from. A
+/// zero result takes the XOR of the operand signs, as IEEE 754 requires.
+pub fn mul_softfloats(
+    a: &SoftFloat,
+    b: &SoftFloat,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, ParsedValue) {
+    if let Some(nan) = propagated_nan(a, b, spec) {
+        return (nan, ParsedValue::Nan);
+    }
+    let exact = mul_values(&softfloat_to_parsed(a, spec), &softfloat_to_parsed(b, spec));
+    let product = parsed_to_softfloat(&exact, spec, rounding);
+    (with_zero_sign(product, a.sign != b.sign), exact)
+}
+
+/// Rounded `a - n*b` (see `remainder_values`). A zero result keeps the sign
+/// of `a`.
+pub fn remainder_softfloats(
+    a: &SoftFloat,
+    b: &SoftFloat,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+    quotient: QuotientRounding,
+) -> SoftFloat {
+    if let Some(nan) = propagated_nan(a, b, spec) {
+        return nan;
+    }
+    let exact = remainder_values(
+        &softfloat_to_parsed(a, spec),
+        &softfloat_to_parsed(b, spec),
+        quotient,
+    );
+    with_zero_sign(parsed_to_softfloat(&exact, spec, rounding), a.sign)
+}
+
+/// The result of an operation with a NaN operand: the first NaN operand,
+/// quieted, with its sign and payload kept, as IEEE 754 recommends. Formats
+/// with a single NaN pattern have no payload to carry. `None` when neither
+/// operand is NaN.
+fn propagated_nan(a: &SoftFloat, b: &SoftFloat, spec: &FloatSpec) -> Option<SoftFloat> {
+    let nan = [a, b].into_iter().find(|sf| sf.class == Class::Nan)?;
+    // A zero fraction means no payload was recorded (a parsed "nan").
+    if spec.nan_encoding != NanPolicy::AllOnesExponent || nan.significand.is_zero() {
+        return Some(nan.clone());
+    }
+    let Some(top) = spec.significand_bits.checked_sub(1) else {
+        return Some(nan.clone());
+    };
+    let quiet_bit = BigUint::one() << top;
+    Some(SoftFloat {
+        significand: &nan.significand | quiet_bit,
+        ..nan.clone()
+    })
+}
+
+/// One-line summary of a value: its stored decimal (or class) and hex encoding.
+pub fn describe_softfloat(
+    sf: &SoftFloat,
+    spec: &FloatSpec,
+    display: &DisplayOptions,
+) -> Result<String> {
+    let value = match softfloat_to_rational(sf, spec) {
+        Some(_) if sf.class == Class::Zero && sf.sign => "-0".to_string(),
+        Some(v) => format_rational(&v, display),
+        None => format!("{:?}", sf.class),
+    };
+    Ok(format!(
+        "{value} (0x{})",
+        display
+            .hex_case
+            .apply(&bits_to_hex(&softfloat_to_bits(sf, spec)?))
+    ))
+}
+
+pub fn softfloat_to_parsed(sf: &SoftFloat, spec: &FloatSpec) -> ParsedValue {
+    match sf.class {
+        Class::PosInfinity => ParsedValue::PosInfinity,
+        Class::NegInfinity => ParsedValue::NegInfinity,
+        Class::Nan | Class::PseudoInfinity | Class::PseudoNan => ParsedValue::Nan,
+        _ => ParsedValue::Finite(
+            softfloat_to_rational(sf, spec).expect("finite classes have a rational value"),
+        ),
+    }
+}
+
+/// Exact extended-real sum; opposite infinities give NaN.
+fn add_values(a: &ParsedValue, b: &ParsedValue) -> ParsedValue {
+    match (a, b) {
+        (ParsedValue::Nan, _) | (_, ParsedValue::Nan) => ParsedValue::Nan,
+        (ParsedValue::PosInfinity, ParsedValue::NegInfinity)
+        | (ParsedValue::NegInfinity, ParsedValue::PosInfinity) => ParsedValue::Nan,
+        (ParsedValue::PosInfinity, _) | (_, ParsedValue::PosInfinity) => ParsedValue::PosInfinity,
+        (ParsedValue::NegInfinity, _) | (_, ParsedValue::NegInfinity) => ParsedValue::NegInfinity,
+        (ParsedValue::Finite(x), ParsedValue::Finite(y)) => ParsedValue::Finite(x + y),
+    }
+}
+
+/// Exact extended-real product; zero times infinity gives NaN.
+pub fn mul_values(a: &ParsedValue, b: &ParsedValue) -> ParsedValue {
+    let negative = |v: &ParsedValue| match v {
+        ParsedValue::Finite(x) => x.is_negative(),
+        ParsedValue::NegInfinity => true,
+        _ => false,
+    };
+    let is_zero = |v: &ParsedValue| matches!(v, ParsedValue::Finite(x) if x.is_zero());
+    match (a, b) {
+        (ParsedValue::Nan, _) | (_, ParsedValue::Nan) => ParsedValue::Nan,
+        (ParsedValue::Finite(x), ParsedValue::Finite(y)) => ParsedValue::Finite(x * y),
+        _ if is_zero(a) || is_zero(b) => ParsedValue::Nan,
+        _ if negative(a) != negative(b) => ParsedValue::NegInfinity,
+        _ => ParsedValue::PosInfinity,
+    }
+}
+
+/// `q` rounded to an integer the way `rounding` picks a remainder quotient.
+fn round_to_integer(q: &BigRational, rounding: QuotientRounding) -> BigRational {
+    match rounding {
+        QuotientRounding::TowardZero => q.trunc(),
+        QuotientRounding::NearestEven => {
+            let floor = q.floor();
+            let half = BigRational::new(BigInt::one(), BigInt::from(2));
+            match (q - &floor).cmp(&half) {
+                Ordering::Less => floor,
+                Ordering::Greater => floor + BigRational::one(),
+                Ordering::Equal if (floor.to_integer() % 2u32).is_zero() => floor,
+                Ordering::Equal => floor + BigRational::one(),
+            }
+        }
+    }
+}
+
+/// Block floating point layout: `block` elements share one exponent and each
+/// keeps a signed `mant`-bit integer magnitude. Parsed from `block=N,mant=M`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BfpLayout {
+    pub block: usize,
+    pub mantissa_bits: usize,
+}
+
+impl FromStr for BfpLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let mut block = None;
+        let mut mantissa_bits = None;
+        for part in raw.split(',') {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected key=value in BFP layout, got {part:?}"))?;
+            let value: usize = value
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid number in BFP layout: {part:?}"))?;
+            match key.trim() {
+                "block" => block = Some(value),
+                "mant" => mantissa_bits = Some(value),
+                other => bail!("unknown BFP layout key {other:?} (expected block or mant)"),
+            }
+        }
+        let block = block.ok_or_else(|| anyhow!("BFP layout is missing block=N"))?;
+        let mantissa_bits = mantissa_bits.ok_or_else(|| anyhow!("BFP layout is missing mant=M"))?;
+        if block == 0 {
+            bail!("BFP block size must be at least 1");
+        }
+        if mantissa_bits == 0 {
+            bail!("BFP mantissa width must be at least 1 bit");
+        }
+        Ok(BfpLayout {
+            block,
+            mantissa_bits,
+        })
+    }
+}
+
+/// One encoded block: element `i` represents `mantissas[i] × 2^(shared_exponent - mant + 1)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BfpBlock {
+    pub shared_exponent: i32,
+    pub mantissas: Vec<BigInt>,
+}
+
+/// Split `values` into blocks and encode each against the exponent of its
+/// largest magnitude. If rounding pushes that element to `2^mant` the shared
+/// exponent is bumped and the block re-encoded. All-zero blocks use exponent 0.
+pub fn encode_bfp(
+    values: &[BigRational],
+    layout: &BfpLayout,
+    rounding: RoundingMode,
+) -> Vec<BfpBlock> {
+    let quotient = match rounding {
+        RoundingMode::HalfEven => QuotientRounding::NearestEven,
+        RoundingMode::TowardZero => QuotientRounding::TowardZero,
+    };
+    let limit = BigInt::one() << layout.mantissa_bits;
+    values
+        .chunks(layout.block)
+        .map(|chunk| {
+            let max = chunk
+                .iter()
+                .map(|v| v.abs())
+                .max()
+                .unwrap_or_else(BigRational::zero);
+            let mut shared_exponent = if max.is_zero() { 0 } else { log2_floor(&max) };
+            loop {
+                let scale = pow2(shared_exponent - layout.mantissa_bits as i32 + 1);
+                let mantissas: Vec<BigInt> = chunk
+                    .iter()
+                    .map(|v| round_to_integer(&(v / &scale), quotient).to_integer())
+                    .collect();
+                if mantissas.iter().all(|m| m.abs() < limit) {
+                    return BfpBlock {
+                        shared_exponent,
+                        mantissas,
+                    };
+                }
+                shared_exponent += 1;
+            }
+        })
+        .collect()
+}
+
+/// Exact `a - n*b` for the integer quotient `n` chosen by `quotient`.
+/// A zero divisor or infinite dividend gives NaN; an infinite divisor leaves `a`.
+pub fn remainder_values(
+    a: &ParsedValue,
+    b: &ParsedValue,
+    quotient: QuotientRounding,
+) -> ParsedValue {
+    match (a, b) {
+        (ParsedValue::Finite(x), ParsedValue::Finite(y)) if !y.is_zero() => {
+            let q = x / y;
+            let n = round_to_integer(&q, quotient);
+            ParsedValue::Finite(x - n * y)
+        }
+        (ParsedValue::Finite(x), ParsedValue::PosInfinity | ParsedValue::NegInfinity) => {
+            ParsedValue::Finite(x.clone())
+        }
+        _ => ParsedValue::Nan,
+    }
+}
+
+/// True when an exact finite result was too large for the format and
+/// quantized to an infinity.
+pub fn overflowed(exact: &ParsedValue, rounded: &SoftFloat) -> bool {
+    matches!(exact, ParsedValue::Finite(_)) && rounded.class.is_infinite()
+}
+
+/// IEEE 754 exception flags a conversion can raise.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+pub enum Exception {
+    Inexact,
+    Overflow,
+    Underflow,
+    Invalid,
+}
+
+/// Flags raised by rounding `source` to `result`. Underflow uses tininess
+/// before rounding and is only signalled together with inexact; an infinite
+/// input that becomes NaN in a format without infinities is invalid.
+pub fn conversion_exceptions(
+    source: &ParsedValue,
+    result: &SoftFloat,
+    spec: &FloatSpec,
+) -> Vec<Exception> {
+    let mut flags = Vec::new();
+    match source {
+        ParsedValue::Finite(v) => {
+            let stored = softfloat_to_rational(result, spec);
+            if stored.as_ref() != Some(v) {
+                flags.push(Exception::Inexact);
+                if stored.is_none() {
+                    flags.push(Exception::Overflow);
+                } else if !v.is_zero()
+                    && compare_pow2(&v.abs(), min_exponent(spec)) == Ordering::Less
+                {
+                    flags.push(Exception::Underflow);
+                }
+            }
+        }
+        ParsedValue::PosInfinity | ParsedValue::NegInfinity => {
+            if result.class == Class::Nan {
+                flags.push(Exception::Invalid);
+            }
+        }
+        ParsedValue::Nan => {}
+    }
+    flags
+}
+
+/// Running tally over a batch of conversions: how many raised each
+/// exception, how many landed on a subnormal, and the largest error in ULPs
+/// of the stored value (finite results only).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub inexact: usize,
+    pub overflow: usize,
+    pub underflow: usize,
+    pub subnormal: usize,
+    pub max_ulp_error: Option<BigRational>,
+}
+
+impl BatchSummary {
+    pub fn record(&mut self, source: &ParsedValue, result: &SoftFloat, spec: &FloatSpec) {
+        self.total += 1;
+        for flag in conversion_exceptions(source, result, spec) {
+            match flag {
+                Exception::Inexact => self.inexact += 1,
+                Exception::Overflow => self.overflow += 1,
+                Exception::Underflow => self.underflow += 1,
+                Exception::Invalid => {}
+            }
+        }
+        if result.class == Class::Subnormal {
+            self.subnormal += 1;
+        }
+        if let (ParsedValue::Finite(v), Some(stored)) =
+            (source, softfloat_to_rational(result, spec))
+        {
+            let (_, _, ulps) = error_in_ulps(&(&stored - v), &stored, spec);
+            let ulps = ulps.abs();
+            if self.max_ulp_error.as_ref().is_none_or(|max| ulps > *max) {
+                self.max_ulp_error = Some(ulps);
+            }
+        }
+    }
+}
+
+/// Rounded sum `s` plus the error term `e = (a + b) - s` rounded to the format,
+/// flagged with whether `e` was representable exactly. `e` is `None` when `s`
+/// is not finite.
+pub fn two_sum_softfloats(
+    a: &SoftFloat,
+    b: &SoftFloat,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, Option<(SoftFloat, bool)>) {
+    if let Some(nan) = propagated_nan(a, b, spec) {
+        return (nan, None);
+    }
+    let exact = add_values(&softfloat_to_parsed(a, spec), &softfloat_to_parsed(b, spec));
+    let sum = parsed_to_softfloat(&exact, spec, rounding);
+    // An exact zero sum is -0 only for (-0) + (-0); x + (-x) is +0 in both
+    // supported rounding modes. Sums that underflow keep their own sign.
+    let sum = match &exact {
+        ParsedValue::Finite(x) if x.is_zero() => with_zero_sign(
+            sum,
+            a.class == Class::Zero && a.sign && b.class == Class::Zero && b.sign,
+        ),
+        _ => sum,
+    };
+    let err = match (&exact, softfloat_to_rational(&sum, spec)) {
+        (ParsedValue::Finite(x), Some(s)) => {
+            let residual = x - &s;
+            let e = parsed_to_softfloat(&ParsedValue::Finite(residual.clone()), spec, rounding);
+            let representable = softfloat_to_rational(&e, spec) == Some(residual);
+            Some((e, representable))
+        }
+        _ => None,
+    };
+    (sum, err)
+}
+
+/// How much of the operands' leading significand a subtraction `a - b`
+/// wiped out, measured on the exact difference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cancellation {
+    /// Binades lost between the larger operand and the difference; every
+    /// significant bit (`significand_bits + 1`) when the difference is zero.
+    pub bits: u32,
+    /// `(|a| + |b|) / |a - b|`, the factor by which relative errors already in
+    /// the operands grow in the difference. `None` for a zero difference.
+    pub amplification: Option<BigRational>,
+}
+
+/// Cancellation in `a - b`, or `None` unless both operands are finite.
+pub fn subtraction_cancellation(
+    a: &SoftFloat,
+    b: &SoftFloat,
+    spec: &FloatSpec,
+) -> Option<Cancellation> {
+    let a = softfloat_to_rational(a, spec)?;
+    let b = softfloat_to_rational(b, spec)?;
+    let diff = (&a - &b).abs();
+    if diff.is_zero() {
+        let bits = if a.is_zero() {
+            0
+        } else {
+            spec.significand_bits as u32 + 1
+        };
+        return Some(Cancellation {
+            bits,
+            amplification: None,
+        });
+    }
+    let top = [&a, &b]
+        .into_iter()
+        .filter(|v| !v.is_zero())
+        .map(|v| log2_floor(&v.abs()))
+        .max()
+        .unwrap_or(i32::MIN);
+    Some(Cancellation {
+        bits: (top - log2_floor(&diff)).max(0) as u32,
+        amplification: Some((a.abs() + b.abs()) / diff),
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum ParsedValue {
+    Finite(BigRational),
+    PosInfinity,
+    NegInfinity,
+    Nan,
+}
+
+pub fn preset_spec(choice: FormatChoice) -> Option<FloatSpec> {
+    let spec = match choice {
+        FormatChoice::Fp16 => FloatSpec {
+            name: "FP16",
+            exponent_bits: 5,
+            significand_bits: 10,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 15,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Bfloat16 => FloatSpec {
+            name: "bfloat16",
+            exponent_bits: 8,
+            significand_bits: 7,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 127,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp32 => FloatSpec {
+            name: "FP32",
+            exponent_bits: 8,
+            significand_bits: 23,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 127,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp64 => FloatSpec {
+            name: "FP64",
+            exponent_bits: 11,
+            significand_bits: 52,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 1023,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Tf32 => FloatSpec {
+            name: "TensorFloat-32",
+            exponent_bits: 8,
+            significand_bits: 10,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 127,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp24 => FloatSpec {
+            name: "FP24",
+            exponent_bits: 7,
+            significand_bits: 16,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 63,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp8E4m3 => FloatSpec {
+            name: "FP8 E4M3",
+            exponent_bits: 4,
+            significand_bits: 3,
+            has_infinity: false,
+            nan_encoding: NanPolicy::AllOnesPattern,
+            bias: 7,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp8E5m2 => FloatSpec {
+            name: "FP8 E5M2",
+            exponent_bits: 5,
+            significand_bits: 2,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 15,
+            explicit_integer_bit: false,
+        },
+        FormatChoice::Fp80 => FloatSpec {
+            name: "FP80",
+            exponent_bits: 15,
+            significand_bits: 63,
+            has_infinity: true,
+            nan_encoding: NanPolicy::AllOnesExponent,
+            bias: 16383,
+            explicit_integer_bit: true,
+        },
+        FormatChoice::Custom => return None,
+    };
+    Some(spec)
+}
+
+pub fn custom_spec(e: usize, s: usize) -> Result<FloatSpec> {
+    if !(1..=11).contains(&e) {
+        bail!("exponent bits must be between 1 and 11");
+    }
+    if !(1..=52).contains(&s) {
+        bail!("significand bits must be between 1 and 52");
+    }
+    Ok(FloatSpec {
+        name: "Custom",
+        exponent_bits: e,
+        significand_bits: s,
+        has_infinity: true,
+        nan_encoding: NanPolicy::AllOnesExponent,
+        bias: standard_bias(e),
+        explicit_integer_bit: false,
+    })
+}
+
+pub fn check_strict_ieee(spec: &FloatSpec) -> Result<()> {
+    if !spec.has_infinity {
+        bail!(
+            "{} is not an IEEE 754 format: it has no infinities (--strict-ieee)",
+            spec.name
+        );
+    }
+    if spec.nan_encoding != NanPolicy::AllOnesExponent {
+        bail!(
+            "{} is not an IEEE 754 format: not every all-ones exponent pattern is NaN (--strict-ieee)",
+            spec.name
+        );
+    }
+    if spec.bias != standard_bias(spec.exponent_bits) {
+        bail!(
+            "{} is not an IEEE 754 format: bias {} is not {} (--strict-ieee)",
+            spec.name,
+            spec.bias,
+            standard_bias(spec.exponent_bits)
+        );
+    }
+    if spec.explicit_integer_bit {
+        bail!(
+            "{} is not an IEEE 754 interchange format: it stores an explicit integer bit (--strict-ieee)",
+            spec.name
+        );
+    }
+    Ok(())
+}
+
+pub fn total_bits(spec: &FloatSpec) -> Result<usize> {
+    Ok(1 + spec.exponent_bits + usize::from(spec.explicit_integer_bit) + spec.significand_bits)
+}
+
+/// Encodes `raw` with the platform parser; it must target the matching layout.
+pub fn native_bits(raw: &str, native: NativeFloat, spec: &FloatSpec) -> Result<String> {
+    let trimmed = raw.trim();
+    let (name, exponent_bits, significand_bits) = match native {
+        NativeFloat::F32 => ("f32", 8, 23),
+        NativeFloat::F64 => ("f64", 11, 52),
+    };
+    if spec.exponent_bits != exponent_bits
+        || spec.significand_bits != significand_bits
+        || spec.bias != standard_bias(exponent_bits)
+    {
+        bail!(
+            "--native {name} requires a format with {exponent_bits} exponent and {significand_bits} significand bits and the standard bias"
+        );
+    }
+    let bits = match native {
+        NativeFloat::F32 => {
+            let v = f32::from_str(trimmed)
+                .with_context(|| format!("unable to parse {name} input: {raw}"))?;
+            format!("{:032b}", v.to_bits())
+        }
+        NativeFloat::F64 => {
+            let v = f64::from_str(trimmed)
+                .with_context(|| format!("unable to parse {name} input: {raw}"))?;
+            format!("{:064b}", v.to_bits())
+        }
+    };
+    Ok(bits)
+}
+
+/// The crate's exact encoding of `raw` next to the platform parser's, with
+/// their distance in ULPs (`None` when either side is NaN).
+#[derive(Debug, Clone)]
+pub struct NativeComparison {
+    pub engine: SoftFloat,
+    pub native: SoftFloat,
+    pub ulps: Option<BigUint>,
+}
+
+/// `value` as an exact `digits` or `digitse-k` literal, or `None` unless its
+/// denominator divides a power of ten (every parsed decimal does).
+fn decimal_literal(value: &BigRational) -> Option<String> {
+    let mut rest = value.denom().clone();
+    let twos = rest.trailing_zeros().unwrap_or(0);
+    rest >>= twos;
+    let mut fives = 0u64;
+    while (&rest % 5u32).is_zero() {
+        rest /= 5u32;
+        fives += 1;
+    }
+    if !rest.is_one() {
+        return None;
+    }
+    let places = twos.max(fives);
+    let digits = value.numer()
+        * BigInt::from(2u32).pow((places - twos) as u32)
+        * BigInt::from(5u32).pow((places - fives) as u32);
+    Some(if places == 0 {
+        digits.to_string()
+    } else {
+        format!("{digits}e-{places}")
+    })
+}
+
+/// Converts `raw` through the big-rational engine and through
+/// `str::parse::<f32>()`/`<f64>()`, whichever matches `spec`'s layout. The
+/// platform parser sees the decimal the crate read, so spellings only the
+/// crate accepts (`1_000`, `50%`) compare as well.
+pub fn compare_native(
+    raw: &str,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> Result<NativeComparison> {
+    let native = match (spec.exponent_bits, spec.significand_bits) {
+        (8, 23) => NativeFloat::F32,
+        (11, 52) => NativeFloat::F64,
+        _ => bail!(
+            "--compare-native needs the FP32 or FP64 layout, not {}",
+            spec.name
+        ),
+    };
+    let parsed = parse_decimal(raw)?;
+    let canonical = match &parsed {
+        ParsedValue::Finite(v) => {
+            decimal_literal(v).ok_or_else(|| anyhow!("{raw} has no exact decimal form"))?
+        }
+        ParsedValue::PosInfinity => "inf".to_string(),
+        ParsedValue::NegInfinity => "-inf".to_string(),
+        ParsedValue::Nan => "NaN".to_string(),
+    };
+    let native = bits_to_softfloat(&native_bits(&canonical, native, spec)?, spec)?;
+    let engine = parsed_to_softfloat(&parsed, spec, rounding);
+    let (_, ulps) = compare_softfloats(&engine, &native, spec, false)?;
+    Ok(NativeComparison {
+        engine,
+        native,
+        ulps,
+    })
+}
+
+/// Rejects separators that would collide with digits, signs, exponents, or the
+/// `_` digit grouping the decimal parser already accepts.
+pub fn validate_decimal_separator(separator: char) -> Result<()> {
+    if separator.is_alphanumeric() || "+-_/%".contains(separator) || separator.is_whitespace() {
+        bail!("'{separator}' cannot be used as a decimal separator");
+    }
+    Ok(())
+}
+
+/// Rewrites `raw` to use `.` as its decimal separator.
+pub fn delocalize_decimal(raw: &str, separator: char) -> Result<String> {
+    if separator == '.' {
+        return Ok(raw.to_string());
+    }
+    if raw.contains('.') {
+        bail!("unexpected '.' in {raw}; the decimal separator is '{separator}'");
+    }
+    Ok(raw.replace(separator, "."))
+}
+
+pub fn parse_decimal_with(raw: &str, separator: char) -> Result<ParsedValue> {
+    parse_decimal(&delocalize_decimal(raw, separator)?)
+}
+
+/// The spellings accepted for non-finite input, matched case-insensitively
+/// after an optional `+` is stripped. Every special-value name lives here.
+fn parse_special(unsigned: &str) -> Option<ParsedValue> {
+    match unsigned.to_ascii_lowercase().as_str() {
+        "inf" | "infinity" | "∞" => Some(ParsedValue::PosInfinity),
+        "-inf" | "-infinity" | "-∞" => Some(ParsedValue::NegInfinity),
+        "nan" => Some(ParsedValue::Nan),
+        _ => None,
+    }
+}
+
+/// The one error every parser raises for blank input, so `--bits ""`, an
+/// empty `--value-file`, and a zero-byte dump all read the same way.
+fn empty_input(what: &str) -> anyhow::Error {
+    anyhow!("empty input: expected {what}")
+}
+
+/// Largest decimal exponent kept exactly; `10^1000000` is already 3.3M bits.
+const MAX_DECIMAL_EXPONENT: u32 = 1_000_000;
+
+/// `int / 10^places` in lowest terms. Any common factor is a power of 2 and
+/// of 5, so those are divided out directly: a gcd against a denominator like
+/// `10^1000000` would take minutes.
+fn decimal_fraction(int: BigInt, places: u32) -> BigRational {
+    if int.is_zero() {
+        return BigRational::zero();
+    }
+    let twos = int.trailing_zeros().unwrap_or(0).min(u64::from(places)) as u32;
+    let mut numer = int >> twos;
+    let mut fives = 0;
+    while fives < places && (&numer % 5u32).is_zero() {
+        numer /= 5u32;
+        fives += 1;
+    }
+    let denom = (BigInt::one() << (places - twos)) * BigInt::from(5u32).pow(places - fives);
+    BigRational::new_raw(numer, denom)
+}
+
+pub fn parse_decimal(raw: &str) -> Result<ParsedValue> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err(empty_input("a decimal"));
+    }
+    // Pasted values often carry a space between the sign and the digits;
+    // reject that explicitly instead of surfacing BigDecimal's parse error.
+    let mut chars = trimmed.chars();
+    if let (Some('+' | '-'), Some(next)) = (chars.next(), chars.next())
+        && next.is_whitespace()
+    {
+        bail!("unexpected space after the sign in decimal input: {raw:?}");
+    }
+    let unsigned = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    // A single trailing `%` scales by 1/100 exactly. It is not combined with
+    // an exponent, where `1e2%` would be easy to misread.
+    let (unsigned, percent) = match unsigned.strip_suffix('%') {
+        Some(body) => {
+            if body.contains('%') {
+                bail!("decimal input may contain at most one trailing '%': {raw:?}");
+            }
+            if body.contains(['e', 'E']) {
+                bail!("percentages cannot use scientific notation: {raw:?}");
+            }
+            (body, true)
+        }
+        None if unsigned.contains('%') => {
+            bail!("'%' is only allowed at the end of decimal input: {raw:?}")
+        }
+        None => (unsigned, false),
+    };
+    match parse_special(unsigned) {
+        Some(_) if percent => bail!("special values cannot be percentages: {raw:?}"),
+        Some(special) => Ok(special),
+        None => {
+            let dec = BigDecimal::from_str(unsigned)
+                // Without std the bigdecimal error is not an Error type, so
+                // it is carried as the message of the cause instead.
+                .map_err(|err| {
+                    anyhow!("{err}").context(format!("unable to parse decimal input: {raw}"))
+                })?;
+            let (int, exp) = dec.into_bigint_and_exponent();
+            // `exp` counts decimal places; a percentage adds two more.
+            let exp = if percent { exp.saturating_add(2) } else { exp };
+            let places = u32::try_from(exp.unsigned_abs())
+                .ok()
+                .filter(|&places| places <= MAX_DECIMAL_EXPONENT)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "decimal exponent out of range (at most ±{MAX_DECIMAL_EXPONENT}): {raw}"
+                    )
+                })?;
+            let rat = if exp >= 0 {
+                decimal_fraction(int, places)
+            } else {
+                BigRational::from_integer(int * BigInt::from(10u32).pow(places))
+            };
+            Ok(ParsedValue::Finite(rat))
+        }
+    }
+}
+
+/// Splits `a+bi` (or `a-bi`) into its real and imaginary decimals. The split
+/// is the last sign that is neither leading nor part of an exponent; a bare
+/// `i` coefficient means 1.
+pub fn parse_complex(raw: &str) -> Result<(ParsedValue, ParsedValue)> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err(empty_input("a complex a+bi"));
+    }
+    let Some(body) = trimmed.strip_suffix('i') else {
+        bail!("complex input must end with 'i', as in 1.5+2.5i: {raw:?}");
+    };
+    let bytes = body.as_bytes();
+    let split = (1..bytes.len())
+        .rev()
+        .find(|&i| matches!(bytes[i], b'+' | b'-') && !matches!(bytes[i - 1], b'e' | b'E'))
+        .with_context(|| format!("complex input needs both parts, as in 1.5+2.5i: {raw:?}"))?;
+    let (real, imag) = body.split_at(split);
+    let imag = match imag {
+        "+" => "1",
+        "-" => "-1",
+        imag => imag,
+    };
+    Ok((parse_decimal(real)?, parse_decimal(imag)?))
+}
+
+/// A complex value quantized part by part, with the squared magnitude of the
+/// combined error when both parts are finite.
+#[derive(Debug, Clone)]
+pub struct ComplexQuantization {
+    pub real: SoftFloat,
+    pub imag: SoftFloat,
+    pub error_squared: Option<BigRational>,
+}
+
+/// Rounds the real and imaginary parts independently to `spec`.
+pub fn quantize_complex(
+    real: &ParsedValue,
+    imag: &ParsedValue,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> ComplexQuantization {
+    let part = |value: &ParsedValue| {
+        let soft = parsed_to_softfloat(value, spec, rounding);
+        let error = match (value, softfloat_to_rational(&soft, spec)) {
+            (ParsedValue::Finite(exact), Some(stored)) => Some(stored - exact),
+            _ => None,
+        };
+        (soft, error)
+    };
+    let (real, real_error) = part(real);
+    let (imag, imag_error) = part(imag);
+    let error_squared = real_error
+        .zip(imag_error)
+        .map(|(re, im)| &re * &re + &im * &im);
+    ComplexQuantization {
+        real,
+        imag,
+        error_squared,
+    }
+}
+
+pub fn parsed_to_softfloat(
+    value: &ParsedValue,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> SoftFloat {
+    let sign = match value {
+        ParsedValue::Finite(v) => v.is_negative(),
+        ParsedValue::PosInfinity => false,
+        ParsedValue::NegInfinity => true,
+        ParsedValue::Nan => false,
+    };
+
+    match value {
+        ParsedValue::Nan => SoftFloat {
+            class: Class::Nan,
+            sign: false,
+            exponent: 0,
+            significand: BigUint::zero(),
+        },
+        ParsedValue::PosInfinity | ParsedValue::NegInfinity => overflow_result(sign, spec),
+        ParsedValue::Finite(v) if v.is_zero() => SoftFloat {
+            class: Class::Zero,
+            sign,
+            exponent: min_exponent(spec),
+            significand: BigUint::zero(),
+        },
+        ParsedValue::Finite(v) => quantize_finite(v, spec, rounding).0,
+    }
+}
+
+/// Rounds a nonzero finite `v` into `spec`, also returning whether rounding
+/// carried out of the fraction field (see `rounding_carries`).
+fn quantize_finite(v: &BigRational, spec: &FloatSpec, rounding: RoundingMode) -> (SoftFloat, bool) {
+    let sign = v.is_negative();
+    let abs = v.abs();
+    let max_exp = max_exponent(spec);
+    let min_norm = min_exponent(spec);
+
+    // The bit counts bound log2(abs) to [estimate - 1, estimate + 1),
+    // so inputs like 1e100000 resolve here before any shifting by
+    // their full magnitude. Anything under half the smallest
+    // subnormal rounds like a proxy of the same size class.
+    let estimate = abs.numer().bits() as i64 - abs.denom().bits() as i64;
+    if estimate - 1 > i64::from(max_exp) {
+        return (overflow_result(sign, spec), false);
+    }
+    let half_min_subnormal = i64::from(min_norm) - spec.significand_bits as i64 - 1;
+    if estimate < half_min_subnormal {
+        let proxy = pow2(half_min_subnormal as i32 - 1);
+        return quantize_subnormal(&proxy, sign, spec, rounding);
+    }
+
+    let exp = log2_floor(&abs);
+
+    if exp > max_exp {
+        return (overflow_result(sign, spec), false);
+    }
+
+    if exp >= min_norm {
+        quantize_normal(&abs, sign, exp, spec, rounding)
+    } else {
+        quantize_subnormal(&abs, sign, spec, rounding)
+    }
+}
+
+/// The strict counterpart to `parsed_to_softfloat`: packs `value` only when
+/// `spec` holds it exactly, and errors instead of rounding otherwise.
+/// Infinities and NaN need a format that encodes them.
+pub fn parsed_to_softfloat_exact(value: &ParsedValue, spec: &FloatSpec) -> Result<SoftFloat> {
+    let soft = parsed_to_softfloat(value, spec, RoundingMode::HalfEven);
+    let exact = match value {
+        ParsedValue::Finite(v) => softfloat_to_rational(&soft, spec).as_ref() == Some(v),
+        ParsedValue::PosInfinity | ParsedValue::NegInfinity => soft.class.is_infinite(),
+        ParsedValue::Nan => soft.class == Class::Nan && spec.nan_encoding != NanPolicy::None,
+    };
+    if !exact {
+        bail!("{} does not represent the input exactly", spec.name);
+    }
+    Ok(soft)
+}
+
+/// Infinity of the given sign, or NaN for formats without infinities, or the
+/// largest finite value of that sign for formats with neither.
+fn overflow_result(sign: bool, spec: &FloatSpec) -> SoftFloat {
+    if !spec.has_infinity && spec.nan_encoding == NanPolicy::None {
+        return SoftFloat {
+            class: Class::Normal,
+            sign,
+            exponent: max_exponent(spec),
+            significand: (BigUint::one() << spec.significand_bits) - BigUint::one(),
+        };
+    }
+    if !spec.has_infinity {
+        return SoftFloat {
+            significand: BigUint::zero(),
+            sign,
+            ..canonical_nan(spec)
+        };
+    }
+    SoftFloat {
+        class: if sign {
+            Class::NegInfinity
+        } else {
+            Class::PosInfinity
+        },
+        sign,
+        exponent: max_exponent(spec) + 1,
+        significand: BigUint::zero(),
+    }
+}
+
+/// Whether a normal-range exponent and fraction collide with the NaN pattern
+/// of an `AllOnesPattern` format (all-ones exponent and fraction).
+fn is_nan_encoding(exponent: i32, significand: &BigUint, spec: &FloatSpec) -> bool {
+    spec.nan_encoding == NanPolicy::AllOnesPattern
+        && exponent == max_exponent(spec)
+        && *significand == (BigUint::one() << spec.significand_bits) - BigUint::one()
+}
+
+fn bias(spec: &FloatSpec) -> i32 {
+    spec.bias
+}
+
+/// The IEEE 754 bias `2^(w-1) - 1` for a `w`-bit exponent field.
+pub fn standard_bias(exponent_bits: usize) -> i32 {
+    (1i32 << (exponent_bits - 1)) - 1
+}
+
+/// `spec` with its bias replaced. The bias must be a value the exponent field
+/// can hold, `[0, 2^exponent_bits - 1]`. Both ends are accepted even though
+/// 1.0 then no longer has a normal encoding: bias 0 puts it on the all-zero
+/// (subnormal) field and the all-ones bias puts it on the all-ones field.
+pub fn with_bias(spec: &FloatSpec, bias: i32) -> Result<FloatSpec> {
+    let all_ones = (1i32 << spec.exponent_bits) - 1;
+    if !(0..=all_ones).contains(&bias) {
+        bail!(
+            "bias {bias} is outside [0, {all_ones}] for a {}-bit exponent field",
+            spec.exponent_bits
+        );
+    }
+    Ok(FloatSpec {
+        bias,
+        ..spec.clone()
+    })
+}
+
+/// `spec` with its special values redeclared. Infinities take the all-ones
+/// exponent with a zero fraction, so the other patterns under that exponent
+/// must then be NaN (`AllOnesExponent`); without infinities any policy works.
+pub fn with_specials(
+    spec: &FloatSpec,
+    has_infinity: bool,
+    nan_encoding: NanPolicy,
+) -> Result<FloatSpec> {
+    if has_infinity && nan_encoding != NanPolicy::AllOnesExponent {
+        bail!(
+            "a format with infinities reserves the all-ones exponent, so its NaN encoding must be all-ones-exponent"
+        );
+    }
+    Ok(FloatSpec {
+        has_infinity,
+        nan_encoding,
+        ..spec.clone()
+    })
+}
+
+pub fn min_exponent(spec: &FloatSpec) -> i32 {
+    1 - bias(spec)
+}
+
+/// Exponent extremes of a format: the largest and smallest normal binades and
+/// the weight of the smallest subnormal's only set bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentRange {
+    pub max_normal: i32,
+    pub min_normal: i32,
+    pub min_subnormal: i32,
+}
+
+pub fn exponent_range(spec: &FloatSpec) -> ExponentRange {
+    ExponentRange {
+        max_normal: max_exponent(spec),
+        min_normal: min_exponent(spec),
+        min_subnormal: min_exponent(spec) - spec.significand_bits as i32,
+    }
+}
+
+/// Where gradual underflow begins: the largest subnormal sits exactly one
+/// subnormal ULP below the smallest normal `2^min_normal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnderflowBoundary {
+    pub max_subnormal: BigRational,
+    pub min_normal: BigRational,
+    pub subnormal_ulp: BigRational,
+}
+
+/// `None` when the format has no subnormals (no fraction field) or no normals.
+pub fn underflow_boundary(spec: &FloatSpec) -> Option<UnderflowBoundary> {
+    if spec.significand_bits == 0 || !has_normal_binade(spec) {
+        return None;
+    }
+    let min_normal = pow2(min_exponent(spec));
+    let subnormal_ulp = pow2(min_exponent(spec) - spec.significand_bits as i32);
+    Some(UnderflowBoundary {
+        max_subnormal: &min_normal - &subnormal_ulp,
+        min_normal,
+        subnormal_ulp,
+    })
+}
+
+/// Outcome of `self_test`: how many checks ran and a description of each
+/// one that failed.
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: usize,
+    pub failures: Vec<String>,
+}
+
+/// SplitMix64, so the random battery is reproducible from `seed` alone.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Runs a battery of encodings through the conversion core: the special
+/// values, every power of two, the first subnormals, and `random_samples`
+/// random bit patterns. Each one must re-encode to the same bits after a
+/// decode, finite values must round back to themselves, and the next pattern
+/// up must lie strictly above, with the midpoint rounding to one of the two
+/// to nearest and to the smaller magnitude toward zero.
+pub fn self_test(spec: &FloatSpec, random_samples: usize, seed: u64) -> Result<SelfTestReport> {
+    let total = total_bits(spec)?;
+    let exact =
+        |v: BigRational| parsed_to_softfloat(&ParsedValue::Finite(v), spec, RoundingMode::HalfEven);
+    let range = exponent_range(spec);
+    let mut battery: Vec<SoftFloat> = special_values(spec)
+        .into_iter()
+        .filter_map(|(_, sf)| sf)
+        .collect();
+    battery.extend((range.min_subnormal..=range.max_normal).map(|k| exact(pow2(k))));
+    battery.extend((1..=8u32).map(|k| exact(pow2(range.min_subnormal) * BigInt::from(k))));
+    let mut state = seed;
+    for _ in 0..random_samples {
+        let bits: String = (0..total)
+            .map(|_| {
+                if splitmix64(&mut state) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect();
+        battery.push(bits_to_softfloat(&bits, spec)?);
+    }
+
+    let mut report = SelfTestReport::default();
+    let mut check = |ok: bool, failure: &dyn Fn() -> String| {
+        report.checks += 1;
+        if !ok {
+            report.failures.push(failure());
+        }
+    };
+    for sf in &battery {
+        let bits = softfloat_to_bits(sf, spec)?;
+        let hex = bits_to_hex(&bits);
+        let decoded = bits_to_softfloat(&bits, spec)?;
+        let again = softfloat_to_bits(&decoded, spec)?;
+        check(again == bits, &|| {
+            format!("0x{hex} re-encodes as 0x{}", bits_to_hex(&again))
+        });
+
+        // Pseudo encodings have no canonical value or ordinal to check.
+        let Some(value) = softfloat_to_rational(sf, spec).filter(|_| !sf.class.is_pseudo()) else {
+            continue;
+        };
+        let rounded = exact(value.clone());
+        let stable = if sf.class == Class::Zero {
+            rounded.class == Class::Zero
+        } else {
+            softfloat_to_bits(&rounded, spec)? == bits
+        };
+        check(stable, &|| format!("0x{hex} does not round back to itself"));
+
+        let Some(ordinal) = softfloat_ordinal(sf, spec)? else {
+            continue;
+        };
+        let Ok(up) = ordinal_to_softfloat(&(&ordinal + 1), spec) else {
+            continue;
+        };
+        let Some(up_value) = softfloat_to_rational(&up, spec) else {
+            continue;
+        };
+        check(up_value > value, &|| {
+            format!("next value after 0x{hex} is not larger")
+        });
+        check(softfloat_ordinal(&up, spec)? == Some(&ordinal + 1), &|| {
+            format!("next pattern after 0x{hex} has the wrong ordinal")
+        });
+        let mid = ParsedValue::Finite((&value + &up_value) / BigInt::from(2));
+        let nearest = softfloat_to_rational(
+            &parsed_to_softfloat(&mid, spec, RoundingMode::HalfEven),
+            spec,
+        );
+        check(
+            nearest == Some(value.clone()) || nearest == Some(up_value.clone()),
+            &|| format!("midpoint above 0x{hex} rounds outside its neighbours"),
+        );
+        let truncated = softfloat_to_rational(
+            &parsed_to_softfloat(&mid, spec, RoundingMode::TowardZero),
+            spec,
+        );
+        let smaller = if up_value.abs() < value.abs() {
+            &up_value
+        } else {
+            &value
+        };
+        check(truncated.as_ref() == Some(smaller), &|| {
+            format!("midpoint above 0x{hex} does not truncate toward zero")
+        });
+    }
+    Ok(report)
+}
+
+/// Well-known constants for eyeball checks against published tables: π, e,
+/// and √2 to 64 significant digits (far beyond what rounding to FP64 needs),
+/// plus the format's own smallest subnormal and largest finite value.
+pub fn reference_constants(
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> Vec<(&'static str, SoftFloat)> {
+    let decimals = [
+        (
+            "Pi",
+            "3.141592653589793238462643383279502884197169399375105820974944592",
+        ),
+        (
+            "E",
+            "2.718281828459045235360287471352662497757247093699959574966967628",
+        ),
+        (
+            "Sqrt2",
+            "1.414213562373095048801688724209698078569671875376948073176679738",
+        ),
+    ];
+    let mut constants: Vec<(&'static str, SoftFloat)> = decimals
+        .iter()
+        .map(|&(name, digits)| {
+            let value = parse_decimal(digits).expect("constant digits parse");
+            (name, parsed_to_softfloat(&value, spec, rounding))
+        })
+        .collect();
+    let extremes = [
+        ("Min subnorm", pow2(exponent_range(spec).min_subnormal)),
+        ("Max finite", max_finite(spec)),
+    ];
+    for (name, value) in extremes {
+        constants.push((
+            name,
+            parsed_to_softfloat(&ParsedValue::Finite(value), spec, rounding),
+        ));
+    }
+    constants
+}
+
+/// The landmark encodings of a format, built from its parameters: both zeros,
+/// the subnormal and normal extremes, the infinities, and the canonical NaN.
+/// Entries the format cannot encode are `None`.
+pub fn special_values(spec: &FloatSpec) -> Vec<(&'static str, Option<SoftFloat>)> {
+    let min_exp = min_exponent(spec);
+    let at_min = |class, sign, significand| SoftFloat {
+        class,
+        sign,
+        exponent: min_exp,
+        significand,
+    };
+    let all_ones = (BigUint::one() << spec.significand_bits) - BigUint::one();
+    let normals = has_normal_binade(spec);
+    let max_normal = normals.then(|| {
+        parsed_to_softfloat(
+            &ParsedValue::Finite(max_finite(spec)),
+            spec,
+            RoundingMode::HalfEven,
+        )
+    });
+    let infinity = |sign| spec.has_infinity.then(|| overflow_result(sign, spec));
+    let nan = (spec.nan_encoding != NanPolicy::None).then(|| canonical_nan(spec));
+    vec![
+        ("+0", Some(at_min(Class::Zero, false, BigUint::zero()))),
+        ("-0", Some(at_min(Class::Zero, true, BigUint::zero()))),
+        (
+            "Min subnorm",
+            Some(at_min(Class::Subnormal, false, BigUint::one())),
+        ),
+        (
+            "Max subnorm",
+            Some(at_min(Class::Subnormal, false, all_ones)),
+        ),
+        (
+            "Min normal",
+            normals.then(|| at_min(Class::Normal, false, BigUint::zero())),
+        ),
+        ("Max normal", max_normal),
+        ("+Inf", infinity(false)),
+        ("-Inf", infinity(true)),
+        ("NaN", nan),
+    ]
+}
+
+/// The narrowest standard-bias layout that stores a value exactly as a normal
+/// number. `significant_bits` counts the implicit leading bit, so a format
+/// needs `significant_bits - 1` fraction bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinFormat {
+    pub significant_bits: u64,
+    pub exponent_bits: usize,
+}
+
+/// `None` when `value` is not dyadic (its denominator is not a power of two,
+/// like 0.1), since then no binary format holds it exactly. Zero fits the
+/// smallest layout.
+pub fn min_format(value: &BigRational) -> Option<MinFormat> {
+    if value.is_zero() {
+        return Some(MinFormat {
+            significant_bits: 1,
+            exponent_bits: 2,
+        });
+    }
+    let denom = value.denom();
+    if denom.trailing_zeros() != Some(denom.bits() - 1) {
+        return None;
+    }
+    let numer = value.numer().magnitude();
+    let odd = numer >> numer.trailing_zeros().unwrap_or(0);
+    let exponent = log2_floor(&value.abs());
+    // Normal exponents under the standard bias span [2 - 2^(e-1), 2^(e-1) - 1].
+    let exponent_bits = (2usize..)
+        .find(|&e| {
+            let half = 1i64 << (e - 1);
+            (2 - half..half).contains(&(exponent as i64))
+        })
+        .expect("some exponent width covers any i32 exponent");
+    Some(MinFormat {
+        significant_bits: odd.bits(),
+        exponent_bits,
+    })
+}
+
+/// Exponent of the binade containing `abs`, clamped to the format's normal range.
+/// Zero and subnormal magnitudes share the minimum exponent.
+pub fn binade_exponent(abs: &BigRational, spec: &FloatSpec) -> i32 {
+    if abs.is_zero() {
+        return min_exponent(spec);
+    }
+    log2_floor(abs).clamp(min_exponent(spec), max_exponent(spec))
+}
+
+/// Distance between adjacent representable values around `abs`.
+pub fn ulp_gap(abs: &BigRational, spec: &FloatSpec) -> BigRational {
+    pow2(binade_exponent(abs, spec) - spec.significand_bits as i32)
+}
+
+/// The positive normal values with unbiased exponent `exp`, in increasing
+/// order: one per significand field, so `2^significand_bits` of them in a
+/// full binade. Encodings the format reserves for NaN are skipped, and an
+/// exponent outside the normal range yields nothing. Lazy, so wide formats
+/// can be sampled with `take`.
+pub fn values_in_binade(exp: i32, spec: &FloatSpec) -> impl Iterator<Item = BigRational> + '_ {
+    let in_range = (min_exponent(spec)..=max_exponent(spec)).contains(&exp);
+    let count = if in_range {
+        BigUint::one() << spec.significand_bits
+    } else {
+        BigUint::zero()
+    };
+    core::iter::successors(Some(BigUint::zero()), |k| Some(k + 1u32))
+        .take_while(move |k| *k < count)
+        .filter(move |k| !is_nan_encoding(exp, k, spec))
+        .filter_map(move |significand| {
+            let sf = SoftFloat {
+                class: Class::Normal,
+                sign: false,
+                exponent: exp,
+                significand,
+            };
+            softfloat_to_rational(&sf, spec)
+        })
+}
+
+/// An integer is exact when its significant bits (ignoring trailing zeros) fit
+/// in the significand plus the implicit bit and its binade is finite.
+pub fn integer_is_exact(n: &BigInt, spec: &FloatSpec) -> bool {
+    if n.is_zero() {
+        return true;
+    }
+    let magnitude = n.magnitude();
+    let trailing = magnitude.trailing_zeros().unwrap_or(0);
+    let significant = magnitude.bits() - trailing;
+    let top_exponent = magnitude.bits() as i64 - 1;
+    significant <= spec.significand_bits as u64 + 1 && top_exponent <= max_exponent(spec) as i64
+}
+
+/// Splits `err` into whole ULPs of the stored value's binade plus a remainder
+/// of the same sign, and also returns the error measured in ULPs.
+pub fn error_in_ulps(
+    err: &BigRational,
+    stored: &BigRational,
+    spec: &FloatSpec,
+) -> (BigInt, BigRational, BigRational) {
+    let ulp = ulp_gap(&stored.abs(), spec);
+    let total = err / &ulp;
+    let whole = total.trunc();
+    let remainder = err - &whole * &ulp;
+    (whole.to_integer(), remainder, total)
+}
+
+/// `stored - baseline` exactly, and that difference in ULPs of the stored
+/// value's binade. With the original input as the baseline this is the
+/// conversion error.
+pub fn baseline_difference(
+    stored: &BigRational,
+    baseline: &BigRational,
+    spec: &FloatSpec,
+) -> (BigRational, BigRational) {
+    let diff = stored - baseline;
+    let ulps = &diff / ulp_gap(&stored.abs(), spec);
+    (diff, ulps)
+}
+
+/// Largest finite value: all-ones fraction in the top normal binade, one ULP
+/// lower when that pattern is the NaN of an `AllOnesPattern` format, or the
+/// largest subnormal when there are no normals.
+fn max_finite(spec: &FloatSpec) -> BigRational {
+    if !has_normal_binade(spec) {
+        return (BigRational::one() - pow2(-(spec.significand_bits as i32)))
+            * pow2(min_exponent(spec));
+    }
+    let ulps = if spec.nan_encoding == NanPolicy::AllOnesPattern {
+        2
+    } else {
+        1
+    };
+    (pow2(1) - pow2(-(spec.significand_bits as i32)) * BigInt::from(ulps))
+        * pow2(max_exponent(spec))
+}
+
+/// `abs` as a fraction of the largest finite value.
+pub fn range_fraction(abs: &BigRational, spec: &FloatSpec) -> BigRational {
+    abs / max_finite(spec)
+}
+
+/// Where log2(`abs`) sits between the smallest subnormal (0.0) and the largest
+/// finite value (1.0). Zero maps to 0.0.
+pub fn log_range_fraction(abs: &BigRational, spec: &FloatSpec) -> f64 {
+    if abs.is_zero() {
+        return 0.0;
+    }
+    let log2 = |v: &BigRational| {
+        let exp = log2_floor(v);
+        let mantissa = (v / pow2(exp)).to_f64().unwrap_or(1.0);
+        exp as f64 + Float::log2(mantissa)
+    };
+    let low = (min_exponent(spec) - spec.significand_bits as i32) as f64;
+    let high = log2(&max_finite(spec));
+    ((log2(abs) - low) / (high - low)).max(0.0)
+}
+
+fn log2_floor(r: &BigRational) -> i32 {
+    let num_bits = r.numer().bits() as i32;
+    let den_bits = r.denom().bits() as i32;
+    let mut exp = num_bits - den_bits - 1;
+
+    loop {
+        let cmp_low = compare_pow2(r, exp);
+        let cmp_high = compare_pow2(r, exp + 1);
+        if (cmp_low != Ordering::Less) && cmp_high == Ordering::Less {
+            return exp;
+        }
+        if cmp_low == Ordering::Less {
+            exp -= 1;
+        } else {
+            exp += 1;
+        }
+    }
+}
+
+pub fn compare_pow2(r: &BigRational, exp: i32) -> Ordering {
+    let pow = BigInt::one() << exp.abs();
+    if exp >= 0 {
+        r.numer().cmp(&(r.denom() * pow))
+    } else {
+        (r.numer() * pow).cmp(r.denom())
+    }
+}
+
+/// The same input rounded under each supported mode, in declaration order.
+pub fn all_roundings(value: &ParsedValue, spec: &FloatSpec) -> Vec<(RoundingMode, SoftFloat)> {
+    [RoundingMode::HalfEven, RoundingMode::TowardZero]
+        .into_iter()
+        .map(|mode| (mode, parsed_to_softfloat(value, spec, mode)))
+        .collect()
+}
+
+/// Quantize onto a `k`-bit significand grid with the same exponent range, then
+/// place the result in the full-width field with the low bits zeroed, as a
+/// datapath that truncates its mantissa would.
+pub fn round_to_bits(
+    value: &ParsedValue,
+    spec: &FloatSpec,
+    k: usize,
+    rounding: RoundingMode,
+) -> Result<SoftFloat> {
+    if !(1..=spec.significand_bits).contains(&k) {
+        bail!(
+            "--round-to-bits must be between 1 and {} for {}",
+            spec.significand_bits,
+            spec.name
+        );
+    }
+    let narrow = FloatSpec {
+        significand_bits: k,
+        ..*spec
+    };
+    let mut soft = parsed_to_softfloat(value, &narrow, rounding);
+    soft.significand <<= spec.significand_bits - k;
+    Ok(soft)
+}
+
+fn quantize_normal(
+    abs: &BigRational,
+    sign: bool,
+    exp: i32,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, bool) {
+    let frac = abs / pow2(exp);
+    // frac should be in [1, 2)
+    let mant = &frac - BigRational::one();
+    let needed = spec.significand_bits + 3;
+    let (bits, sticky) = fraction_bits(&mant, needed);
+    let (mantissa, carry) = round_bits(bits, sticky, spec.significand_bits, rounding);
+
+    let mut exponent = exp;
+    let mut significand = mantissa;
+
+    if carry {
+        exponent += 1;
+        significand = BigUint::zero();
+    }
+
+    if exponent > max_exponent(spec) || is_nan_encoding(exponent, &significand, spec) {
+        return (overflow_result(sign, spec), carry);
+    }
+
+    let soft = SoftFloat {
+        class: Class::Normal,
+        sign,
+        exponent,
+        significand,
+    };
+    (soft, carry)
+}
+
+fn quantize_subnormal(
+    abs: &BigRational,
+    sign: bool,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+) -> (SoftFloat, bool) {
+    let min_exp = min_exponent(spec);
+    let scaled = abs / pow2(min_exp);
+    let needed = spec.significand_bits + 3;
+    let (bits, sticky) = fraction_bits(&scaled, needed);
+    let (mantissa, carry) = round_bits(bits, sticky, spec.significand_bits, rounding);
+
+    if carry {
+        // A 1-bit exponent whose only nonzero field is reserved has no
+        // normal binade to carry into.
+        if !has_normal_binade(spec) {
+            return (overflow_result(sign, spec), true);
+        }
+        // Rounded up into the normal range at the smallest exponent.
+        let soft = SoftFloat {
+            class: Class::Normal,
+            sign,
+            exponent: min_exp,
+            significand: BigUint::zero(),
+        };
+        return (soft, true);
+    }
+
+    let class = if mantissa.is_zero() {
+        Class::Zero
+    } else {
+        Class::Subnormal
+    };
+
+    let soft = SoftFloat {
+        class,
+        sign,
+        exponent: min_exp,
+        significand: mantissa,
+    };
+    (soft, false)
+}
+
+fn pow2(exp: i32) -> BigRational {
+    if exp >= 0 {
+        BigRational::from_integer(BigInt::one() << exp)
+    } else {
+        BigRational::new(BigInt::one(), BigInt::one() << (-exp))
+    }
+}
+
+/// True when the bits discarded by rounding `value` into `spec` are exactly
+/// `1000…0`, i.e. the input is a tie and the rounding mode's tie rule decides.
+pub fn is_exactly_halfway(value: &BigRational, spec: &FloatSpec) -> bool {
+    let abs = value.abs();
+    if abs.is_zero() {
+        return false;
+    }
+    // Far below half the smallest subnormal there is no tie to find, and
+    // the scaling below would be a gcd against the whole denominator.
+    let estimate = abs.numer().bits() as i64 - abs.denom().bits() as i64;
+    if estimate < i64::from(min_exponent(spec)) - spec.significand_bits as i64 - 1 {
+        return false;
+    }
+    let exp = log2_floor(&abs);
+    if exp > max_exponent(spec) {
+        return false;
+    }
+    let min_norm = min_exponent(spec);
+    let frac = if exp >= min_norm {
+        &abs / pow2(exp) - BigRational::one()
+    } else {
+        &abs / pow2(min_norm)
+    };
+    let width = spec.significand_bits;
+    let (bits, sticky) = fraction_bits(&frac, width + 3);
+    bits[width] == 1 && bits[width + 1..].iter().all(|&b| b == 0) && !sticky
+}
+
+/// True when rounding `value` into `spec` carries out of the fraction field:
+/// the kept bits were all ones and rounded up, so the exponent grew by one
+/// and the fraction reset to zero. This includes a subnormal rounding up to
+/// the smallest normal, and a carry out of the top binade that overflows.
+pub fn rounding_carries(value: &BigRational, spec: &FloatSpec, rounding: RoundingMode) -> bool {
+    !value.is_zero() && quantize_finite(value, spec, rounding).1
+}
+
+fn fraction_bits(frac: &BigRational, bits: usize) -> (Vec<u8>, bool) {
+    let mut result = Vec::with_capacity(bits);
+    let mut remainder = frac.clone();
+    let two = BigInt::from(2);
+    for _ in 0..bits {
+        let doubled = &remainder * &two;
+        if doubled >= BigRational::one() {
+            result.push(1);
+            remainder = doubled - BigRational::one();
+        } else {
+            result.push(0);
+            remainder = doubled;
+        }
+    }
+    let sticky = !remainder.is_zero();
+    (result, sticky)
+}
+
+fn round_bits(bits: Vec<u8>, sticky: bool, width: usize, mode: RoundingMode) -> (BigUint, bool) {
+    let kept = &bits[..width];
+    let kept_value = bits_to_uint(kept);
+
+    match mode {
+        RoundingMode::TowardZero => (kept_value, false),
+        RoundingMode::HalfEven => {
+            if width >= bits.len() {
+                return (kept_value, false);
+            }
+            let guard = bits.get(width).copied().unwrap_or(0);
+            let round_bit = bits.get(width + 1).copied().unwrap_or(0);
+            let rest_sticky = sticky || bits.iter().skip(width + 2).any(|b| *b == 1);
+
+            let should_increment = match (guard, round_bit, rest_sticky) {
+                (1, 0, false) => kept.last().copied().unwrap_or(0) == 1,
+                (1, _, _) => true,
+                _ => false,
+            };
+
+            if should_increment {
+                let max_val = (BigUint::one() << width) - BigUint::one();
+                if kept_value == max_val {
+                    (BigUint::zero(), true)
+                } else {
+                    (kept_value + BigUint::one(), false)
+                }
+            } else {
+                (kept_value, false)
+            }
+        }
+    }
+}
+
+fn bits_to_uint(bits: &[u8]) -> BigUint {
+    let mut value = BigUint::zero();
+    for &b in bits {
+        value <<= 1;
+        if b == 1 {
+            value += 1u8;
+        }
+    }
+    value
+}
+
+pub fn softfloat_to_bits(sf: &SoftFloat, spec: &FloatSpec) -> Result<String> {
+    let mut out = String::with_capacity(total_bits(spec)?);
+    out.push(if sf.sign { '1' } else { '0' });
+
+    let exp_bits = spec.exponent_bits;
+    let frac_bits = spec.significand_bits;
+
+    if sf.significand.bits() > frac_bits as u64 {
+        bail!(
+            "significand 0x{:X} does not fit in {} fraction bits",
+            sf.significand,
+            frac_bits
+        );
+    }
+    if sf.class.is_pseudo() && !spec.explicit_integer_bit {
+        bail!(
+            "{} has no explicit integer bit to encode {:?}",
+            spec.name,
+            sf.class
+        );
+    }
+
+    let fraction = if frac_bits == 0 {
+        String::new()
+    } else {
+        format!("{:0width$b}", sf.significand, width = frac_bits)
+    };
+    let (exponent_field, integer_bit, fraction) = match sf.class {
+        Class::PosInfinity | Class::NegInfinity => {
+            if !spec.has_infinity {
+                bail!("{} has no infinity encoding", spec.name);
+            }
+            ("1".repeat(exp_bits), '1', "0".repeat(frac_bits))
+        }
+        Class::Nan => {
+            if spec.nan_encoding == NanPolicy::None {
+                bail!("{} has no NaN encoding", spec.name);
+            }
+            if sf.significand.is_zero() || spec.nan_encoding == NanPolicy::AllOnesPattern {
+                // No payload recorded (e.g. parsed "nan"): use an all-ones fraction.
+                ("1".repeat(exp_bits), '1', "1".repeat(frac_bits))
+            } else {
+                ("1".repeat(exp_bits), '1', fraction)
+            }
+        }
+        Class::Zero | Class::Subnormal => ("0".repeat(exp_bits), '0', fraction),
+        Class::PseudoDenormal => ("0".repeat(exp_bits), '1', fraction),
+        Class::PseudoInfinity => ("1".repeat(exp_bits), '0', "0".repeat(frac_bits)),
+        Class::PseudoNan => ("1".repeat(exp_bits), '0', fraction),
+        Class::Normal | Class::Unnormal => {
+            let biased = sf.exponent + bias(spec);
+            if biased < 1
+                || sf.exponent > max_exponent(spec)
+                || is_nan_encoding(sf.exponent, &sf.significand, spec)
+            {
+                bail!(
+                    "exponent {} is outside the normal range of {}",
+                    sf.exponent,
+                    spec.name
+                );
+            }
+            let integer_bit = if sf.class == Class::Normal { '1' } else { '0' };
+            (
+                format!("{:0width$b}", biased, width = exp_bits),
+                integer_bit,
+                fraction,
+            )
+        }
+    };
+
+    out.push_str(&exponent_field);
+    if spec.explicit_integer_bit {
+        out.push(integer_bit);
+    }
+    out.push_str(&fraction);
+    Ok(out)
+}
+
+pub fn bits_to_hex(bits: &str) -> String {
+    let hex = bits_to_fixed_hex(bits);
+    let trimmed = hex.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Upper-case hex with one nibble per four bits of the field, leading zeros kept.
+fn bits_to_fixed_hex(bits: &str) -> String {
+    let padded_len = bits.len().div_ceil(4) * 4;
+    let mut padded = bits.to_string();
+    while padded.len() < padded_len {
+        padded.insert(0, '0');
+    }
+
+    padded
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| {
+            let s = core::str::from_utf8(chunk).unwrap();
+            let v = u8::from_str_radix(s, 2).unwrap();
+            format!("{:X}", v)
+        })
+        .collect()
+}
+
+/// One field of a packed encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    Sign,
+    Exponent,
+    /// The fraction, preceded by the explicit integer bit if the format has one.
+    Significand,
+}
+
+/// Order of the fields in a packed word, most significant first. Everything
+/// else in the crate works on the standard `sign,exp,frac` order; `pack` and
+/// `unpack` translate at the edges. Parsed from e.g. `frac,exp,sign`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldOrder(pub [Field; 3]);
+
+impl Default for FieldOrder {
+    fn default() -> Self {
+        FieldOrder([Field::Sign, Field::Exponent, Field::Significand])
+    }
+}
+
+impl FromStr for FieldOrder {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> Result<Self> {
+        let mut fields = Vec::new();
+        for part in raw.split(',') {
+            let field = match part.trim().to_ascii_lowercase().as_str() {
+                "sign" => Field::Sign,
+                "exp" | "exponent" => Field::Exponent,
+                "frac" | "mant" | "significand" => Field::Significand,
+                other => {
+                    bail!("unknown field {other:?} in field order (expected sign, exp, or frac)")
+                }
+            };
+            if fields.contains(&field) {
+                bail!("field order names {part:?} more than once");
+            }
+            fields.push(field);
+        }
+        let fields: [Field; 3] = fields
+            .try_into()
+            .map_err(|_| anyhow!("field order must name sign, exp, and frac once each"))?;
+        Ok(FieldOrder(fields))
+    }
+}
+
+impl FieldOrder {
+    /// The field's slice of standard-order `bits`.
+    fn field<'a>(bits: &'a str, field: Field, spec: &FloatSpec) -> &'a str {
+        match field {
+            Field::Sign => &bits[..1],
+            Field::Exponent => &bits[1..1 + spec.exponent_bits],
+            Field::Significand => &bits[1 + spec.exponent_bits..],
+        }
+    }
+
+    fn width(field: Field, spec: &FloatSpec) -> usize {
+        match field {
+            Field::Sign => 1,
+            Field::Exponent => spec.exponent_bits,
+            Field::Significand => usize::from(spec.explicit_integer_bit) + spec.significand_bits,
+        }
+    }
+
+    /// Rearranges standard-order `bits` into this order.
+    pub fn pack(&self, bits: &str, spec: &FloatSpec) -> String {
+        self.0
+            .iter()
+            .map(|&field| Self::field(bits, field, spec))
+            .collect()
+    }
+
+    /// Rearranges a packed word in this order back into the standard order.
+    pub fn unpack(&self, packed: &str, spec: &FloatSpec) -> Result<String> {
+        let packed = strip_binary_prefix(packed);
+        if packed.is_empty() {
+            return Err(empty_input("bits"));
+        }
+        if !packed.bytes().all(|b| b == b'0' || b == b'1') {
+            bail!("bits must contain only 0 or 1");
+        }
+        let total = total_bits(spec)?;
+        if packed.len() != total {
+            bail!("expected {} bits, got {}", total, packed.len());
+        }
+        let mut slices = [""; 3];
+        let mut offset = 0;
+        for &field in &self.0 {
+            let width = Self::width(field, spec);
+            slices[field as usize] = &packed[offset..offset + width];
+            offset += width;
+        }
+        Ok(slices.concat())
+    }
+}
+
+/// A Verilog/SystemVerilog constant for the encoding, with the sign,
+/// exponent, and fraction fields separated by `_` in a trailing comment:
+/// `localparam [15:0] VALUE = 16'h3E00; // 0_01111_1000000000`. `bits` is in
+/// the standard order; the constant and comment follow `order`. An explicit
+/// integer bit gets a field of its own.
+pub fn verilog_localparam(
+    bits: &str,
+    spec: &FloatSpec,
+    order: FieldOrder,
+    hex_case: HexCase,
+) -> String {
+    let width = bits.len();
+    let fields: Vec<&str> = order
+        .0
+        .iter()
+        .flat_map(|&field| {
+            let slice = FieldOrder::field(bits, field, spec);
+            if field == Field::Significand && spec.explicit_integer_bit {
+                vec![&slice[..1], &slice[1..]]
+            } else {
+                vec![slice]
+            }
+        })
+        .collect();
+    format!(
+        "localparam [{}:0] VALUE = {width}'h{}; // {}",
+        width - 1,
+        hex_case.apply(&bits_to_fixed_hex(&order.pack(bits, spec))),
+        fields.join("_")
+    )
+}
+
+/// A C snippet reproducing the encoding. FP16/FP32/FP64 layouts are copied
+/// into `_Float16`/`float`/`double` with `memcpy`; any other layout is emitted
+/// as the raw integer with a comment naming the format.
+pub fn c_snippet(bits: &str, spec: &FloatSpec, hex_case: HexCase) -> String {
+    let width = bits.len();
+    let storage = match width {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=32 => 32,
+        33..=64 => 64,
+        _ => {
+            return format!(
+                "/* {} ({width}-bit): wider than uint64_t, raw encoding 0x{} */",
+                spec.name,
+                hex_case.apply(&bits_to_fixed_hex(bits))
+            );
+        }
+    };
+    let suffix = if storage == 64 { "ull" } else { "u" };
+    let hex = hex_case.apply(&bits_to_fixed_hex(bits));
+    let constant = format!("const uint{storage}_t bits = 0x{hex}{suffix};");
+    let standard = spec.has_infinity
+        && spec.nan_encoding == NanPolicy::AllOnesExponent
+        && spec.bias == standard_bias(spec.exponent_bits);
+    let native = match (spec.exponent_bits, spec.significand_bits) {
+        (5, 10) if standard => Some("_Float16"),
+        (8, 23) if standard => Some("float"),
+        (11, 52) if standard => Some("double"),
+        _ => None,
+    };
+    match native {
+        Some(ty) => format!("{constant} {ty} f; memcpy(&f, &bits, sizeof f);"),
+        None => format!(
+            "{constant} /* {} ({width}-bit): no standard C type, raw encoding */",
+            spec.name
+        ),
+    }
+}
+
+/// A Python one-liner reproducing the encoding. FP32/FP64 round-trip through
+/// `struct`, FP16 through a numpy `.view(np.float16)`; any other layout is
+/// emitted as the raw integer with a comment naming the format.
+pub fn python_snippet(bits: &str, spec: &FloatSpec, hex_case: HexCase) -> String {
+    let hex = hex_case.apply(&bits_to_fixed_hex(bits));
+    let standard = spec.has_infinity
+        && spec.nan_encoding == NanPolicy::AllOnesExponent
+        && spec.bias == standard_bias(spec.exponent_bits);
+    match (spec.exponent_bits, spec.significand_bits) {
+        (8, 23) if standard => {
+            format!("import struct; struct.unpack('<f', struct.pack('<I', 0x{hex}))[0]")
+        }
+        (11, 52) if standard => {
+            format!("import struct; struct.unpack('<d', struct.pack('<Q', 0x{hex}))[0]")
+        }
+        (5, 10) if standard => {
+            format!("import numpy as np; np.array(0x{hex}, dtype=np.uint16).view(np.float16)")
+        }
+        _ => format!(
+            "0x{hex}  # {} ({}-bit): no Python float type, raw encoding",
+            spec.name,
+            bits.len()
+        ),
+    }
+}
+
+/// Fixed-width hex split into bytes, e.g. `3F C0 00 00`. Bytes are counted
+/// from the least significant end, so an odd nibble count leaves a single
+/// leading nibble.
+pub fn bits_to_hex_bytes(bits: &str) -> String {
+    let hex = bits_to_fixed_hex(bits);
+    let lead = hex.len() % 2;
+    let mut groups = Vec::new();
+    if lead == 1 {
+        groups.push(&hex[..1]);
+    }
+    groups.extend(
+        hex.as_bytes()[lead..]
+            .chunks(2)
+            .map(|pair| core::str::from_utf8(pair).unwrap()),
+    );
+    groups.join(" ")
+}
+
+/// Whether `raw` reads as a complete hex encoding for `spec`: exactly one
+/// digit per nibble of the layout, with an optional `0x`. Used to guess that
+/// a pasted `3FC00000` is bits rather than a decimal.
+pub fn looks_like_encoding(raw: &str, spec: &FloatSpec) -> Result<bool> {
+    let trimmed = raw.trim();
+    let digits = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    Ok(digits.len() == total_bits(spec)?.div_ceil(4)
+        && digits.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+pub fn hex_to_bits(hex: &str, total_bits: usize) -> Result<String> {
+    let trimmed = hex.trim();
+    let cleaned = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    if cleaned.is_empty() {
+        return Err(empty_input("hex digits"));
+    }
+    if let Some(ch) = cleaned.chars().find(|c| !c.is_ascii_hexdigit()) {
+        bail!("hex input contains non-hex character '{ch}'");
+    }
+    let max_nibbles = total_bits.div_ceil(4);
+    if cleaned.len() > max_nibbles {
+        bail!(
+            "too many nibbles for a {total_bits}-bit format (got {}, max {max_nibbles})",
+            cleaned.len()
+        );
+    }
+    let padded = "0".repeat(max_nibbles - cleaned.len()) + cleaned;
+    let mut bits = String::with_capacity(max_nibbles * 4);
+    for ch in padded.chars() {
+        let val = ch.to_digit(16).expect("validated hex digit");
+        bits.push_str(&format!("{:04b}", val));
+    }
+    // Widths that are not a multiple of four leave unused high bits in the
+    // leading nibble; they must be clear.
+    let excess = bits.len() - total_bits;
+    if bits[..excess].contains('1') {
+        bail!("hex value 0x{cleaned} does not fit in a {total_bits}-bit format");
+    }
+    Ok(bits[excess..].to_string())
+}
+
+/// Parses an unsigned field value written in decimal, or in hex/binary with a
+/// `0x`/`0b` prefix.
+pub fn parse_field_value(raw: &str) -> Result<BigUint> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Err(empty_input("a field value"));
+    }
+    let (digits, radix) = if let Some(hex) = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        (hex, 16)
+    } else if let Some(bin) = trimmed
+        .strip_prefix("0b")
+        .or_else(|| trimmed.strip_prefix("0B"))
+    {
+        (bin, 2)
+    } else {
+        (trimmed, 10)
+    };
+    BigUint::parse_bytes(digits.as_bytes(), radix)
+        .with_context(|| format!("unable to parse field value: {raw}"))
+}
+
+/// Assembles a bit pattern from its raw sign, biased exponent, and stored
+/// significand fields, with no rounding. The significand field includes the
+/// integer bit for formats that store it explicitly.
+pub fn assemble_fields(
+    sign: &BigUint,
+    exponent: &BigUint,
+    significand: &BigUint,
+    spec: &FloatSpec,
+) -> Result<String> {
+    let significand_width = spec.significand_bits + usize::from(spec.explicit_integer_bit);
+    let mut out = String::with_capacity(total_bits(spec)?);
+    for (name, value, width) in [
+        ("sign", sign, 1),
+        ("exponent", exponent, spec.exponent_bits),
+        ("significand", significand, significand_width),
+    ] {
+        if value.bits() > width as u64 {
+            bail!("{name} field 0x{value:X} does not fit in {width} bits");
+        }
+        out.push_str(&format!("{value:0width$b}"));
+    }
+    Ok(out)
+}
+
+/// Splits a raw dump of packed values into `total_bits / 8`-byte elements and
+/// decodes each, reading every element's bytes in `endianness` order and its
+/// fields in `field_order`.
+pub fn decode_packed(
+    bytes: &[u8],
+    spec: &FloatSpec,
+    endianness: Endianness,
+    field_order: FieldOrder,
+) -> Result<Vec<SoftFloat>> {
+    let total = total_bits(spec)?;
+    if !total.is_multiple_of(8) {
+        bail!("packed binary data needs a byte-aligned format, not {total} bits");
+    }
+    let size = total / 8;
+    if bytes.is_empty() {
+        return Err(empty_input("packed binary data"));
+    }
+    if !bytes.len().is_multiple_of(size) {
+        bail!(
+            "{} bytes is not a whole number of {size}-byte {} values",
+            bytes.len(),
+            spec.name
+        );
+    }
+    bytes
+        .chunks(size)
+        .map(|chunk| {
+            let bits: String = match endianness {
+                Endianness::Big => chunk.iter().map(|b| format!("{b:08b}")).collect(),
+                Endianness::Little => chunk.iter().rev().map(|b| format!("{b:08b}")).collect(),
+            };
+            bits_to_softfloat(&field_order.unpack(&bits, spec)?, spec)
+        })
+        .collect()
+}
+
+/// Inverse of `decode_packed`: the encodings laid end to end, each element's
+/// bytes in `endianness` order and its fields in `field_order`.
+pub fn encode_packed(
+    values: &[SoftFloat],
+    spec: &FloatSpec,
+    endianness: Endianness,
+    field_order: FieldOrder,
+) -> Result<Vec<u8>> {
+    let total = total_bits(spec)?;
+    if !total.is_multiple_of(8) {
+        bail!("packed binary data needs a byte-aligned format, not {total} bits");
+    }
+    let mut out = Vec::with_capacity(values.len() * total / 8);
+    for value in values {
+        let bits = field_order.pack(&softfloat_to_bits(value, spec)?, spec);
+        let mut bytes: Vec<u8> = bits
+            .as_bytes()
+            .chunks(8)
+            .map(|byte| u8::from_str_radix(core::str::from_utf8(byte).unwrap(), 2).unwrap())
+            .collect();
+        if let Endianness::Little = endianness {
+            bytes.reverse();
+        }
+        out.extend(bytes);
+    }
+    Ok(out)
+}
+
+/// Reverses the byte order of a full-width hex encoding.
+pub fn swap_hex_bytes(hex: &str, total_bits: usize) -> Result<String> {
+    let cleaned = hex.trim().trim_start_matches("0x").trim_start_matches("0X");
+    if !total_bits.is_multiple_of(8) {
+        bail!("byte order only applies to byte-aligned formats, not {total_bits} bits");
+    }
+    if cleaned.len() != total_bits / 4 {
+        bail!(
+            "little-endian hex needs all {} digits for a {total_bits}-bit format, got {}",
+            total_bits / 4,
+            cleaned.len()
+        );
+    }
+    if !cleaned.is_ascii() {
+        bail!("invalid hex input: {hex}");
+    }
+    Ok(cleaned
+        .as_bytes()
+        .chunks(2)
+        .rev()
+        .map(|pair| core::str::from_utf8(pair).unwrap())
+        .collect())
+}
+
+fn strip_binary_prefix(bits: &str) -> &str {
+    let trimmed = bits.trim();
+    trimmed
+        .strip_prefix("0b")
+        .or_else(|| trimmed.strip_prefix("0B"))
+        .unwrap_or(trimmed)
+}
+
+pub fn bits_to_softfloat(bits: &str, spec: &FloatSpec) -> Result<SoftFloat> {
+    let cleaned = strip_binary_prefix(bits);
+    if cleaned.is_empty() {
+        return Err(empty_input("bits"));
+    }
+    let total = total_bits(spec)?;
+    if cleaned.len() != total {
+        bail!("expected {} bits, got {}", total, cleaned.len());
+    }
+    if !cleaned.chars().all(|c| c == '0' || c == '1') {
+        bail!("bits must contain only 0 or 1");
+    }
+
+    let sign = cleaned.as_bytes()[0] == b'1';
+    let exp_bits = &cleaned[1..1 + spec.exponent_bits];
+    let frac_bits = &cleaned[total - spec.significand_bits..];
+    let integer_bit = spec
+        .explicit_integer_bit
+        .then(|| cleaned.as_bytes()[1 + spec.exponent_bits] == b'1');
+
+    let exp_val = usize::from_str_radix(exp_bits, 2)?;
+    // Zero-width fraction fields (E8M0-style scales) hold an implicit zero.
+    let mantissa = if frac_bits.is_empty() {
+        BigUint::zero()
+    } else {
+        BigUint::parse_bytes(frac_bits.as_bytes(), 2)
+            .ok_or_else(|| anyhow!("invalid mantissa bits"))?
+    };
+
+    let all_exp_ones = exp_bits.chars().all(|c| c == '1');
+    let all_exp_zero = exp_bits.chars().all(|c| c == '0');
+    let all_frac_zero = mantissa.is_zero();
+
+    let bias = bias(spec);
+    let min_exp = min_exponent(spec);
+
+    let class;
+    let exponent;
+
+    if all_exp_ones && !exponent_all_ones_reserved(spec) {
+        let all_frac_ones = frac_bits.chars().all(|c| c == '1');
+        class = if all_frac_ones && spec.nan_encoding == NanPolicy::AllOnesPattern {
+            Class::Nan
+        } else {
+            Class::Normal
+        };
+        exponent = max_exponent(spec);
+    } else if all_exp_ones {
+        class = if all_frac_zero && spec.has_infinity {
+            if sign {
+                Class::NegInfinity
+            } else {
+                Class::PosInfinity
+            }
+        } else {
+            Class::Nan
+        };
+        exponent = max_exponent(spec);
+    } else if all_exp_zero {
+        class = if all_frac_zero {
+            Class::Zero
+        } else {
+            Class::Subnormal
+        };
+        exponent = min_exp;
+    } else {
+        class = Class::Normal;
+        exponent = exp_val as i32 - bias;
+    }
+
+    // The stored integer bit must agree with the one the exponent implies.
+    let class = match (class, integer_bit) {
+        (Class::Subnormal | Class::Zero, Some(true)) => Class::PseudoDenormal,
+        (Class::Normal, Some(false)) => Class::Unnormal,
+        (Class::PosInfinity | Class::NegInfinity, Some(false)) => Class::PseudoInfinity,
+        (Class::Nan, Some(false)) => Class::PseudoNan,
+        (class, _) => class,
+    };
+
+    Ok(SoftFloat {
+        class,
+        sign,
+        exponent,
+        significand: mantissa,
+    })
+}
+
+/// Stored values are dyadic, so `m / 2^k` terminates after exactly `k`
+/// decimal places. Zero has no meaningful count.
+pub fn exact_decimal_digits(value: &BigRational) -> Option<u64> {
+    if value.is_zero() {
+        return None;
+    }
+    Some(value.denom().trailing_zeros().unwrap_or(0))
+}
+
+/// Exact `m × 2^e` with `m` an odd integer, e.g. `1 × 2^-24` for the smallest
+/// FP16 subnormal. Much shorter than the decimal expansion of a tiny value.
+pub fn integer_times_pow2(value: &BigRational) -> Option<String> {
+    if value.is_zero() {
+        return None;
+    }
+    let numer = value.numer().clone();
+    let denom_twos = value.denom().trailing_zeros().unwrap_or(0);
+    let (mantissa, exponent) = if denom_twos > 0 {
+        (numer, -(denom_twos as i64))
+    } else {
+        let twos = numer.trailing_zeros().unwrap_or(0);
+        (numer >> twos, twos as i64)
+    };
+    Some(format!("{mantissa} × 2^{exponent}"))
+}
+
+/// The integer bit in front of the stored fraction: explicit in the encoding,
+/// or implied by the class. Infinities and NaN have none.
+fn leading_bit(sf: &SoftFloat) -> Option<char> {
+    match sf.class {
+        Class::Normal | Class::PseudoDenormal => Some('1'),
+        Class::Subnormal | Class::Unnormal | Class::Zero => Some('0'),
+        _ => None,
+    }
+}
+
+/// `±1.f₂ × 2^e` for normals and `±0.f₂ × 2^emin` for subnormals, with the
+/// implicit bit made explicit and trailing fraction zeros dropped.
+/// Infinities and NaN have no such form.
+pub fn binary_scientific(sf: &SoftFloat, spec: &FloatSpec) -> Option<String> {
+    if sf.class == Class::Zero {
+        return Some(format!("{}0₂", if sf.sign { "-" } else { "" }));
+    }
+    let lead = leading_bit(sf)?;
+    let fraction = format!("{:0width$b}", sf.significand, width = spec.significand_bits);
+    let fraction = match fraction.trim_end_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+    let sign = if sf.sign { "-" } else { "" };
+    Some(format!("{sign}{lead}.{fraction}₂ × 2^{}", sf.exponent))
+}
+
+/// Trailing zero bits of the stored fraction field. A zero fraction counts
+/// every field bit, since the leading bit alone carries the value (powers of
+/// two and zero). `None` for infinities and NaN.
+pub fn trailing_zero_bits(sf: &SoftFloat, spec: &FloatSpec) -> Option<u64> {
+    leading_bit(sf)?;
+    Some(
+        sf.significand
+            .trailing_zeros()
+            .unwrap_or(spec.significand_bits as u64),
+    )
+}
+
+/// The significand with its leading bit as one hex integer, e.g. `0xC00000`
+/// for FP32 1.5, where the stored fraction alone is `0x400000`. Sized to hold
+/// `significand_bits + 1` bits. `None` for infinities and NaN.
+pub fn significand_hex(sf: &SoftFloat, spec: &FloatSpec) -> Option<String> {
+    let lead = if leading_bit(sf)? == '1' {
+        BigUint::one() << spec.significand_bits
+    } else {
+        BigUint::zero()
+    };
+    let digits = (spec.significand_bits + 1).div_ceil(4);
+    Some(format!("0x{:0digits$X}", lead | &sf.significand))
+}
+
+/// The whole significand `1.f₂` (or `0.f₂` for zeros and subnormals) with
+/// every stored fraction bit, showing the leading bit the encoding leaves
+/// out. Display only: the packed bits are unchanged.
+pub fn implicit_significand(sf: &SoftFloat, spec: &FloatSpec) -> Option<String> {
+    let lead = leading_bit(sf)?;
+    Some(format!(
+        "{lead}.{:0width$b}₂",
+        sf.significand,
+        width = spec.significand_bits
+    ))
+}
+
+/// Coefficients `[a0; a1, a2, …]` of the regular continued fraction of
+/// `value`, by the Euclidean algorithm on its numerator and denominator.
+/// `a0` is the floor (so negative for negative values); the rest are
+/// positive, and the last of them exceeds 1.
+pub fn continued_fraction(value: &BigRational) -> Vec<BigInt> {
+    let mut numer = value.numer().clone();
+    let mut denom = value.denom().clone();
+    let mut terms = Vec::new();
+    while !denom.is_zero() {
+        let quotient = BigRational::new(numer.clone(), denom.clone())
+            .floor()
+            .to_integer();
+        let remainder = &numer - &quotient * &denom;
+        terms.push(quotient);
+        numer = core::mem::replace(&mut denom, remainder);
+    }
+    terms
+}
+
+/// `[a0; a1, a2]` notation for continued-fraction coefficients, or an empty
+/// string when there are none.
+pub fn format_continued_fraction(terms: &[BigInt]) -> String {
+    let Some((first, rest)) = terms.split_first() else {
+        return String::new();
+    };
+    let rest: Vec<String> = rest.iter().map(BigInt::to_string).collect();
+    if rest.is_empty() {
+        format!("[{first}]")
+    } else {
+        format!("[{first}; {}]", rest.join(", "))
+    }
+}
+
+/// Quiet NaN with a positive sign and zero payload: only the top fraction bit
+/// set, or no fraction at all in a format without one.
+pub fn canonical_nan(spec: &FloatSpec) -> SoftFloat {
+    SoftFloat {
+        class: Class::Nan,
+        sign: false,
+        exponent: max_exponent(spec),
+        significand: if spec.nan_encoding == NanPolicy::AllOnesExponent {
+            match spec.significand_bits.checked_sub(1) {
+                Some(top) => BigUint::one() << top,
+                None => BigUint::zero(),
+            }
+        } else {
+            (BigUint::one() << spec.significand_bits) - BigUint::one()
+        },
+    }
+}
+
+/// Whether the all-ones exponent field is kept for infinities and NaNs rather
+/// than holding a binade of normal values.
+fn exponent_all_ones_reserved(spec: &FloatSpec) -> bool {
+    spec.has_infinity || spec.nan_encoding == NanPolicy::AllOnesExponent
+}
+
+/// False only for a 1-bit exponent that reserves its all-ones field, leaving
+/// zero, subnormals, and the specials.
+fn has_normal_binade(spec: &FloatSpec) -> bool {
+    min_exponent(spec) <= max_exponent(spec)
+}
+
+/// Largest normal exponent; formats that do not reserve the all-ones
+/// exponent field use it for normals too.
+fn max_exponent(spec: &FloatSpec) -> i32 {
+    let all_ones = (1i32 << spec.exponent_bits) - 1;
+    if exponent_all_ones_reserved(spec) {
+        all_ones - 1 - bias(spec)
+    } else {
+        all_ones - bias(spec)
+    }
+}
+
+/// A decoded value with the specials kept distinct instead of collapsed to `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueOrSpecial {
+    Value(BigRational),
+    Infinity { negative: bool },
+    Nan { quiet: bool, payload: BigUint },
+}
+
+impl ValueOrSpecial {
+    /// Finite values become decimal strings; specials become tagged objects
+    /// such as `{"special":"snan","payload":"0x1"}`.
+    pub fn to_json(&self, display: &DisplayOptions) -> serde_json::Value {
+        match self {
+            ValueOrSpecial::Value(v) => json!(format_rational(v, display)),
+            ValueOrSpecial::Infinity { negative } => {
+                json!({ "special": if *negative { "-inf" } else { "+inf" } })
+            }
+            ValueOrSpecial::Nan { quiet, payload } => json!({
+                "special": if *quiet { "qnan" } else { "snan" },
+                "payload": format!("0x{payload:X}"),
+            }),
+        }
+    }
+}
+
+/// Like `softfloat_to_rational`, but infinities and NaNs are described rather
+/// than dropped. A NaN is quiet when its top fraction bit is set; the payload
+/// is the remaining fraction bits as encoded.
+pub fn softfloat_to_value(sf: &SoftFloat, spec: &FloatSpec) -> ValueOrSpecial {
+    match sf.class {
+        Class::PosInfinity => ValueOrSpecial::Infinity { negative: false },
+        Class::NegInfinity => ValueOrSpecial::Infinity { negative: true },
+        Class::Nan | Class::PseudoInfinity | Class::PseudoNan => {
+            let fraction = softfloat_to_bits(sf, spec)
+                .ok()
+                .and_then(|bits| {
+                    BigUint::parse_bytes(&bits.as_bytes()[bits.len() - spec.significand_bits..], 2)
+                })
+                .unwrap_or_else(|| sf.significand.clone());
+            // Without a fraction field the single NaN has no quiet bit or payload.
+            let Some(top) = spec.significand_bits.checked_sub(1) else {
+                return ValueOrSpecial::Nan {
+                    quiet: true,
+                    payload: BigUint::zero(),
+                };
+            };
+            let quiet_bit = BigUint::one() << top;
+            ValueOrSpecial::Nan {
+                quiet: &fraction & &quiet_bit == quiet_bit,
+                payload: fraction & (&quiet_bit - BigUint::one()),
+            }
+        }
+        _ => ValueOrSpecial::Value(
+            softfloat_to_rational(sf, spec).expect("finite classes have a rational value"),
+        ),
+    }
+}
+
+/// Every field of the conversion report, already rendered for display. The
+/// CLI's text and JSON renderers both read from this, and embedders (such as
+/// a wasm demo) can serialize it directly.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConvertReport {
+    pub format: &'static str,
+    pub exponent_bits: usize,
+    pub significand_bits: usize,
+    pub class: String,
+    pub sign: &'static str,
+    pub exponent: ExponentField,
+    pub bits: String,
+    pub hex: String,
+    pub mantissa: String,
+    pub stored: serde_json::Value,
+    pub error: Option<String>,
+    pub exactly_halfway: Option<bool>,
+    pub rounding_carry: Option<bool>,
+}
+
+/// The exponent as both the raw biased field and its meaning.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExponentField {
+    pub field: u64,
+    pub unbiased: i32,
+    pub biased_all_ones: bool,
+    pub biased_all_zero: bool,
+}
+
+impl ExponentField {
+    fn decode(soft: &SoftFloat, bits: &str, spec: &FloatSpec) -> Self {
+        let raw = &bits[1..1 + spec.exponent_bits];
+        ExponentField {
+            field: u64::from_str_radix(raw, 2).unwrap_or(0),
+            unbiased: soft.exponent,
+            biased_all_ones: raw.bytes().all(|b| b == b'1'),
+            biased_all_zero: raw.bytes().all(|b| b == b'0'),
+        }
+    }
+}
+
+impl ConvertReport {
+    /// `stored_value` is passed in rather than recomputed so callers can
+    /// reweight the significand first; `source` is the exact input, if any,
+    /// and `rounding` the mode it was quantized with.
+    pub fn new(
+        soft: &SoftFloat,
+        spec: &FloatSpec,
+        stored_value: Option<&BigRational>,
+        source: Option<&BigRational>,
+        rounding: RoundingMode,
+        display: &DisplayOptions,
+    ) -> Result<Self> {
+        let bits = softfloat_to_bits(soft, spec)?;
+        let hex = display.hex_case.apply(&bits_to_hex(&bits));
+        let stored = match stored_value {
+            Some(v) => ValueOrSpecial::Value(v.clone()),
+            None => softfloat_to_value(soft, spec),
+        };
+        let (error, exactly_halfway, rounding_carry) = match (stored_value, source) {
+            (Some(val), Some(src)) => (
+                Some(format_rational(&stored_minus_source(val, src), display)),
+                Some(is_exactly_halfway(src, spec)),
+                Some(rounding_carries(src, spec, rounding)),
+            ),
+            _ => (None, None, None),
+        };
+        Ok(ConvertReport {
+            format: spec.name,
+            exponent_bits: spec.exponent_bits,
+            significand_bits: spec.significand_bits,
+            class: format!("{:?}", soft.class),
+            sign: if soft.sign { "-" } else { "+" },
+            exponent: ExponentField::decode(soft, &bits, spec),
+            bits,
+            hex,
+            mantissa: soft.significand.to_string(),
+            stored: stored.to_json(display),
+            error,
+            exactly_halfway,
+            rounding_carry,
+        })
+    }
+}
+
+/// `stored - source`. A zero stored value skips the subtraction, whose common
+/// denominator would cost a gcd against inputs like `1e-1000000`.
+fn stored_minus_source(stored: &BigRational, source: &BigRational) -> BigRational {
+    if stored.is_zero() {
+        -source.clone()
+    } else {
+        stored - source
+    }
+}
+
+/// Convert a decimal string into `spec` without touching stdout or the
+/// process environment, so the core path also builds for
+/// `wasm32-unknown-unknown`.
+pub fn convert(
+    input: &str,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+    precision: usize,
+    notation: Notation,
+) -> Result<ConvertReport> {
+    let parsed = parse_decimal(input)?;
+    let soft = parsed_to_softfloat(&parsed, spec, rounding);
+    let display = DisplayOptions {
+        precision,
+        notation,
+        trailing_zeros: false,
+        decimal_separator: '.',
+        hex_case: HexCase::Upper,
+        plus_sign: false,
+    };
+    let source = match &parsed {
+        ParsedValue::Finite(v) => Some(v),
+        _ => None,
+    };
+    let stored = softfloat_to_rational(&soft, spec);
+    ConvertReport::new(&soft, spec, stored.as_ref(), source, rounding, &display)
+}
+
+pub fn softfloat_to_rational(sf: &SoftFloat, spec: &FloatSpec) -> Option<BigRational> {
+    softfloat_to_rational_scaled(sf, spec, &BigRational::one())
+}
+
+/// Like `softfloat_to_rational`, but the stored fraction field is weighted by
+/// `scale / 2^significand_bits` instead of `1 / 2^significand_bits`.
+pub fn softfloat_to_rational_scaled(
+    sf: &SoftFloat,
+    spec: &FloatSpec,
+    scale: &BigRational,
+) -> Option<BigRational> {
+    match sf.class {
+        Class::PosInfinity
+        | Class::NegInfinity
+        | Class::Nan
+        | Class::PseudoInfinity
+        | Class::PseudoNan => None,
+        Class::Zero => Some(BigRational::zero()),
+        Class::Subnormal | Class::Unnormal => {
+            let denom = BigInt::one() << spec.significand_bits;
+            let sig = BigRational::new(BigInt::from(sf.significand.clone()), denom) * scale;
+            let exponent = if sf.class == Class::Unnormal {
+                sf.exponent
+            } else {
+                min_exponent(spec)
+            };
+            let value = sig * pow2(exponent);
+            Some(if sf.sign { -value } else { value })
+        }
+        Class::Normal | Class::PseudoDenormal => {
+            let denom = BigInt::one() << spec.significand_bits;
+            let leading = BigRational::one();
+            let frac = BigRational::new(BigInt::from(sf.significand.clone()), denom) * scale;
+            let sig = leading + frac;
+            let value = sig * pow2(sf.exponent);
+            Some(if sf.sign { -value } else { value })
+        }
+    }
+}
+
+/// `value` as a reduced `num/den`, with the denominator always shown so
+/// integers read `3/1` and exactness never depends on digit counts.
+pub fn format_fraction(value: &BigRational) -> String {
+    format!("{}/{}", value.numer(), value.denom())
+}
+
+/// The exact expansion cut (never rounded) after `display.precision` places,
+/// so precision 0 truncates toward zero and keeps the sign: 0.9 prints `0`
+/// and -0.9 prints `-0`. Scientific notation counts the places after the
+/// leading nonzero digit instead, so 0.9 prints `9e-1` and no nonzero value
+/// prints as zero. With `display.plus_sign`, positive values and zero carry a
+/// leading `+`.
+pub fn format_rational(value: &BigRational, display: &DisplayOptions) -> String {
+    if value.is_zero() {
+        return if display.plus_sign { "+0" } else { "0" }.to_string();
+    }
+
+    let sign = value.is_negative();
+    let abs = value.abs();
+    let mut repr = match display.notation {
+        Notation::Plain => {
+            let (integer, digits) = long_division(abs.numer(), abs.denom(), display);
+            if digits.is_empty() {
+                format!("{integer}")
+            } else {
+                format!("{integer}.{digits}")
+            }
+        }
+        Notation::Scientific => {
+            let exponent = decimal_exponent(&abs);
+            let scale = BigInt::from(10u32).pow(exponent.unsigned_abs());
+            let (lead, digits) = if exponent >= 0 {
+                long_division(abs.numer(), &(abs.denom() * scale), display)
+            } else {
+                long_division(&(abs.numer() * scale), abs.denom(), display)
+            };
+            let digits = if display.trailing_zeros {
+                digits.as_str()
+            } else {
+                digits.trim_end_matches('0')
+            };
+            if digits.is_empty() {
+                format!("{lead}e{exponent:+}")
+            } else {
+                format!("{lead}.{digits}e{exponent:+}")
+            }
+        }
+    };
+    if display.decimal_separator != '.' {
+        repr = repr.replace('.', &display.decimal_separator.to_string());
+    }
+
+    if sign {
+        format!("-{repr}")
+    } else if display.plus_sign {
+        format!("+{repr}")
+    } else {
+        repr
+    }
+}
+
+/// `numer / denom` as its integer part and up to `display.precision`
+/// truncated fraction digits, stopping early once the expansion ends unless
+/// trailing zeros are wanted. Long division on the integers: rational
+/// arithmetic would renormalize through a gcd of the whole denominator at
+/// every digit.
+fn long_division(numer: &BigInt, denom: &BigInt, display: &DisplayOptions) -> (BigInt, String) {
+    let integer = numer / denom;
+    let mut rest = numer % denom;
+    let mut digits = String::new();
+    for _ in 0..display.precision {
+        rest *= 10u32;
+        let digit = &rest / denom;
+        rest -= &digit * denom;
+        digits.push_str(&format!("{digit}"));
+        if rest.is_zero() && !display.trailing_zeros {
+            break;
+        }
+    }
+    (integer, digits)
+}
+
+/// `floor(log10(abs))` for a positive `abs`: estimated from the bit lengths,
+/// then corrected against exact powers of ten.
+fn decimal_exponent(abs: &BigRational) -> i32 {
+    // Whether `abs >= 10^exp`, compared on integers.
+    let at_least = |exp: i32| {
+        let scale = BigInt::from(10u32).pow(exp.unsigned_abs());
+        if exp >= 0 {
+            *abs.numer() >= abs.denom() * scale
+        } else {
+            abs.numer() * scale >= *abs.denom()
+        }
+    };
+    let bits = abs.numer().bits() as i64 - abs.denom().bits() as i64;
+    let mut exp = (bits * 30_103).div_euclid(100_000) as i32;
+    while !at_least(exp) {
+        exp -= 1;
+    }
+    while at_least(exp + 1) {
+        exp += 1;
+    }
+    exp
+}
+
+/// Widest posit `decode_posit` accepts.
+pub const MAX_POSIT_BITS: usize = 256;
+
+/// A posit<n, es> pattern: zero, Not-a-Real, or a value with its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositDecoding {
+    Zero,
+    NaR,
+    Finite(PositFields),
+}
+
+/// The fields of a finite posit, read after two's-complement negation for a
+/// set sign bit. `exponent_bits` can fall short of `es` when the regime runs
+/// into the end of the pattern; the missing low bits are zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositFields {
+    pub sign: bool,
+    pub es: usize,
+    /// `k` in `useed^k`: `m - 1` for a run of `m` ones, `-m` for `m` zeros.
+    pub regime: i64,
+    /// The run plus its terminating bit, when there is one.
+    pub regime_bits: usize,
+    pub exponent: u64,
+    pub exponent_bits: usize,
+    pub fraction: BigUint,
+    pub fraction_bits: usize,
+}
+
+impl PositFields {
+    /// `(-1)^sign * useed^regime * 2^exponent * (1 + fraction)`, with
+    /// `useed = 2^(2^es)`.
+    pub fn value(&self) -> BigRational {
+        let scale = self.regime * (1i64 << self.es) + self.exponent as i64;
+        let value = self.significand() * pow2(scale as i32);
+        if self.sign { -value } else { value }
+    }
+
+    /// The `1.f` factor of the decomposition.
+    pub fn significand(&self) -> BigRational {
+        BigRational::one()
+            + BigRational::new(
+                BigInt::from(self.fraction.clone()),
+                BigInt::one() << self.fraction_bits,
+            )
+    }
+}
+
+/// Splits a posit bit pattern (`n = bits.len()` wide) into sign, regime,
+/// exponent, and fraction. Widths are capped at `MAX_POSIT_BITS`, which keeps
+/// every scale `regime * 2^es + exponent` well inside `i32`.
+pub fn decode_posit(bits: &str, es: usize) -> Result<PositDecoding> {
+    let bits = strip_binary_prefix(bits);
+    if bits.is_empty() {
+        return Err(empty_input("bits"));
+    }
+    if bits.len() < 2 {
+        bail!("a posit needs at least 2 bits");
+    }
+    if bits.len() > MAX_POSIT_BITS {
+        bail!("posits wider than {MAX_POSIT_BITS} bits are not supported");
+    }
+    if es > 16 {
+        bail!("posit exponent size must be at most 16 bits");
+    }
+    if let Some(ch) = bits.chars().find(|&c| c != '0' && c != '1') {
+        bail!("posit input contains non-binary character '{ch}'");
+    }
+    let n = bits.len();
+    let pattern = BigUint::parse_bytes(bits.as_bytes(), 2).unwrap_or_else(BigUint::zero);
+    let sign_bit = BigUint::one() << (n - 1);
+    if pattern.is_zero() {
+        return Ok(PositDecoding::Zero);
+    }
+    if pattern == sign_bit {
+        return Ok(PositDecoding::NaR);
+    }
+    let sign = &pattern & &sign_bit == sign_bit;
+    let magnitude = if sign {
+        (BigUint::one() << n) - &pattern
+    } else {
+        pattern
+    };
+    let body = format!("{magnitude:0n$b}");
+    let body = &body.as_bytes()[1..];
+    let lead = body[0];
+    let run = body.iter().take_while(|&&b| b == lead).count();
+    let regime = if lead == b'1' {
+        run as i64 - 1
+    } else {
+        -(run as i64)
+    };
+    let regime_bits = (run + 1).min(body.len());
+    let rest = &body[regime_bits..];
+    let exponent_bits = es.min(rest.len());
+    let read = |field: &[u8]| BigUint::parse_bytes(field, 2).unwrap_or_else(BigUint::zero);
+    let exponent = read(&rest[..exponent_bits]).to_u64().unwrap_or(0) << (es - exponent_bits);
+    let fraction_field = &rest[exponent_bits..];
+    Ok(PositDecoding::Finite(PositFields {
+        sign,
+        es,
+        regime,
+        regime_bits,
+        exponent,
+        exponent_bits,
+        fraction: read(fraction_field),
+        fraction_bits: fraction_field.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,754 +1,1774 @@
+use afcvt::*;
 use anyhow::{Context, Result, anyhow, bail};
-use bigdecimal::BigDecimal;
-use clap::{Parser, ValueEnum};
-use num_bigint::{BigInt, BigUint, ToBigInt};
+use clap::ArgGroup;
+use clap::builder::ArgPredicate;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use num_bigint::BigInt;
 use num_rational::BigRational;
-use num_traits::{One, Signed, Zero};
+use num_traits::{Signed, ToPrimitive, Zero};
 use std::cmp::Ordering;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Parser, Debug)]
 #[command(
     author,
     version,
-    about = "Rust CLI for arbitrary IEEE754-style floating-point conversion"
+    about = "Rust CLI for arbitrary IEEE754-style floating-point conversion",
+    subcommand_negates_reqs = true,
+    group(ArgGroup::new("encoded").args(["bits", "hex", "native_hex"]))
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Target format (built-ins or custom)
-    #[arg(short, long, default_value = "fp32", value_enum)]
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value = "fp32",
+        default_value_ifs([
+            ("native", ArgPredicate::Equals("f64".into()), "fp64"),
+            ("native_hex", ArgPredicate::IsPresent, "fp64"),
+        ]),
+        value_enum
+    )]
     format: FormatChoice,
 
     /// Exponent bit width (required when --format=custom)
-    #[arg(long = "exp", value_name = "BITS")]
+    #[arg(long = "exp", value_name = "BITS", global = true)]
     exponent_bits: Option<usize>,
 
     /// Significand bit width (required when --format=custom)
-    #[arg(long = "mant", value_name = "BITS")]
+    #[arg(long = "mant", value_name = "BITS", global = true)]
     significand_bits: Option<usize>,
 
     /// Rounding mode used when converting from decimal
-    #[arg(long, default_value = "half-even", value_enum)]
+    #[arg(long, global = true, default_value = "half-even", value_enum)]
     rounding: RoundingMode,
 
-    /// Decimal digits to emit for numeric outputs
-    #[arg(long, default_value_t = 32)]
+    /// Decimal places to emit for numeric outputs; the expansion is truncated, so 0 keeps only the integer part
+    #[arg(long, global = true, default_value_t = 32)]
     precision: usize,
 
+    /// Print exactly N digits after the decimal point (truncated, zero-padded); replaces --precision
+    #[arg(long, global = true, value_name = "N", conflicts_with_all = ["precision", "trailing_zeros"])]
+    digits_after_point: Option<usize>,
+
     /// Use scientific notation for displayed numbers
-    #[arg(long, default_value = "plain", value_enum)]
+    #[arg(long, global = true, default_value = "plain", value_enum)]
     notation: Notation,
 
+    /// Override the exponent bias of the selected format
+    #[arg(long, global = true, allow_hyphen_values = true)]
+    bias: Option<i32>,
+
+    /// Declare that the selected format has no infinities
+    #[arg(long, global = true)]
+    no_infinity: bool,
+
+    /// Override which encodings of the selected format are NaN
+    #[arg(long, global = true, value_enum, value_name = "POLICY")]
+    nan_encoding: Option<NanPolicy>,
+
+    /// Treat the all-ones exponent as one more normal binade (no Inf or NaN)
+    #[arg(long, global = true, conflicts_with_all = ["no_infinity", "nan_encoding"])]
+    no_special_exponent: bool,
+
+    /// Print +0 wherever add/mul/rem/fmod would produce -0
+    #[arg(long, global = true)]
+    normalize_minus_zero: bool,
+
+    /// Reject formats whose special-value encoding departs from IEEE 754
+    #[arg(long, global = true)]
+    strict_ieee: bool,
+
+    /// Keep emitting zeros up to --precision after an exact expansion terminates
+    #[arg(long, global = true)]
+    trailing_zeros: bool,
+
+    /// Character separating integer and fractional digits in input and output
+    #[arg(long, global = true, default_value_t = '.', value_name = "CHAR")]
+    decimal_separator: char,
+
     /// Provide a raw bit string (overrides positional decimal input)
-    #[arg(long, conflicts_with = "hex")]
+    #[arg(long, conflicts_with_all = ["hex", "native_hex"])]
     bits: Option<String>,
 
     /// Provide a hexadecimal encoding of the bits (overrides positional decimal input)
-    #[arg(long, conflicts_with = "bits")]
+    #[arg(long, conflicts_with_all = ["bits", "native_hex"])]
     hex: Option<String>,
 
-    /// Decimal input; ignored when --bits/--hex are given
-    #[arg(value_name = "DECIMAL", required_unless_present_any = ["bits", "hex"])]
-    value: Option<String>,
-}
+    /// Raw sign field (0 or 1); with --exp-field and --mant-field, sets the bits directly
+    #[arg(long, value_name = "INT", requires_all = ["exp_field", "mant_field"], conflicts_with_all = ["bits", "hex", "native_hex", "value_file"])]
+    sign: Option<String>,
 
-#[derive(Copy, Clone, Debug, ValueEnum)]
-enum FormatChoice {
-    Fp16,
-    Bfloat16,
-    Fp32,
-    Fp64,
-    Tf32,
-    Custom,
+    /// Raw biased exponent field, in decimal or 0x/0b notation
+    #[arg(long, value_name = "INT", requires_all = ["sign", "mant_field"])]
+    exp_field: Option<String>,
+
+    /// Raw significand field, in decimal or 0x/0b notation
+    #[arg(long, value_name = "INT", requires_all = ["sign", "exp_field"])]
+    mant_field: Option<String>,
+
+    /// Byte order of the --hex input and of each value in --decode-binary/--encode-binary files
+    #[arg(long, default_value = "big", value_enum)]
+    endianness: Endianness,
+
+    /// Hex dump of a value in memory: little-endian bytes, FP64 unless --format is given
+    #[arg(long, value_name = "HEX", conflicts_with = "endianness")]
+    native_hex: Option<String>,
+
+    /// Parse the decimal with Rust's own f32/f64 parser instead of the exact engine
+    #[arg(long, value_enum, conflicts_with_all = ["bits", "hex", "native_hex"])]
+    native: Option<NativeFloat>,
+
+    /// Replace any NaN with the format's canonical quiet NaN instead of keeping its payload
+    #[arg(long)]
+    canonical_nan: bool,
+
+    /// Report layout
+    #[arg(long, default_value = "text", value_enum)]
+    output: OutputFormat,
+
+    /// Indent the JSON report across lines instead of printing one compact line
+    #[arg(long)]
+    json_pretty: bool,
+
+    /// Also convert the value into this format and summarize what changed
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    to: Option<FormatChoice>,
+
+    /// Exponent bit width of the --to format (required when --to=custom)
+    #[arg(long = "to-exp", value_name = "BITS", requires = "to")]
+    to_exponent_bits: Option<usize>,
+
+    /// Significand bit width of the --to format (required when --to=custom)
+    #[arg(long = "to-mant", value_name = "BITS", requires = "to")]
+    to_significand_bits: Option<usize>,
+
+    /// Order of the sign, exponent, and significand fields in the packed word, e.g. frac,exp,sign
+    #[arg(long, value_name = "ORDER", default_value = "sign,exp,frac")]
+    field_order: FieldOrder,
+
+    /// Treat the input as an integer and report whether the format holds it exactly
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native"])]
+    integer: bool,
+
+    /// Fail instead of rounding when the decimal is not exactly representable
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native", "round_to_bits"])]
+    require_exact: bool,
+
+    /// Round to only this many significand bits, zero-filling the rest of the field
+    #[arg(long, value_name = "K", conflicts_with_all = ["bits", "hex", "native_hex", "native"])]
+    round_to_bits: Option<usize>,
+
+    /// Convert under every rounding mode and tabulate the results
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native"])]
+    show_all_roundings: bool,
+
+    /// Exit with an error if the conversion raises any of these exceptions
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "EXCEPTIONS")]
+    trap: Vec<Exception>,
+
+    /// Also show the conversion error as whole ULPs plus a sub-ULP remainder
+    #[arg(long)]
+    error_ulps: bool,
+
+    /// Read the decimal as a complex a+bi and quantize both parts independently
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native", "encode_binary", "decode_binary"])]
+    complex: bool,
+
+    /// Report the stored value's difference from this decimal, exactly and in ULPs
+    #[arg(long, value_name = "DECIMAL")]
+    relative_to: Option<String>,
+
+    /// Print the encoding as a monotone unsigned key for radix-sorting floats
+    #[arg(long)]
+    monotone_key: bool,
+
+    /// After converting several decimals, print counts of each exception and the worst ULP error
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "sign", "value_file", "decode_binary", "encode_binary"])]
+    summary: bool,
+
+    /// Print the exact parsed input as num/den before it is rounded
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "sign"])]
+    show_input_rational: bool,
+
+    /// List the representables within R ULP steps of the stored value
+    #[arg(long, value_name = "R")]
+    ulp_grid: Option<u32>,
+
+    /// Print the significand including its implicit bit in hex, next to the stored fraction
+    #[arg(long)]
+    significand_hex: bool,
+
+    /// Count the trailing zero bits of the stored fraction field
+    #[arg(long)]
+    trailing_zero_bits: bool,
+
+    /// Print the exact decimal spacing of one ULP at the stored value
+    #[arg(long)]
+    show_ulp: bool,
+
+    /// Print the full significand with its implicit leading bit (display only)
+    #[arg(long)]
+    show_implicit_bit: bool,
+
+    /// Print the stored value as a binary significand times a power of two
+    #[arg(long)]
+    binary_sci: bool,
+
+    /// Print the stored value as a continued fraction [a0; a1, a2, ...]
+    #[arg(long)]
+    continued_fraction: bool,
+
+    /// Show subnormal stored values as an odd integer times a power of two
+    #[arg(long)]
+    trim_denormal_trailing: bool,
+
+    /// Count the decimal digits after the point in the exact stored value
+    #[arg(long)]
+    count_exact_digits: bool,
+
+    /// Decode the encoded input and print only its stored value
+    #[arg(long, requires = "encoded")]
+    reformat: bool,
+
+    /// Letter case for printed hex digits
+    #[arg(long, default_value = "upper", value_enum)]
+    hex_case: HexCase,
+
+    /// Print a leading + on positive stored values and exponents
+    #[arg(long)]
+    plus_sign: bool,
+
+    /// Print the hex field zero-padded and grouped per byte
+    #[arg(long)]
+    hex_bytes: bool,
+
+    /// Also parse with Rust's f32/f64 parser and report whether the bits agree
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native"])]
+    compare_native: bool,
+
+    /// Compare direct bfloat16 rounding with rounding to FP32 first, then to bfloat16
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "native"])]
+    check_bf16_shortcut: bool,
+
+    /// Minimum width of the label column in the text report; longer labels widen it
+    #[arg(long, value_name = "N", default_value_t = 12)]
+    report_width: usize,
+
+    /// Report the magnitude as a percentage of the format's largest finite value
+    #[arg(long)]
+    show_range: bool,
+
+    /// Scale used by --show-range
+    #[arg(long, default_value = "linear", value_enum, requires = "show_range")]
+    range_scale: RangeScale,
+
+    /// Count how many ULPs the stored value lies above or below 1.0
+    #[arg(long)]
+    since_one_ulp: bool,
+
+    /// Show the format's largest and smallest normal exponents and its smallest subnormal exponent
+    #[arg(long)]
+    exponent_range: bool,
+
+    /// Show the exact largest subnormal and smallest normal, one subnormal ULP apart
+    #[arg(long)]
+    underflow_boundary: bool,
+
+    /// Report the fewest significand and exponent bits that hold the input exactly
+    #[arg(long)]
+    min_format: bool,
+
+    /// Encode π, e, √2, and the format's extremes, then exit
+    #[arg(long)]
+    check_constants: bool,
+
+    /// Weight applied to the stored significand field when reconstructing values
+    #[arg(long, value_name = "DECIMAL")]
+    significand_scale: Option<String>,
+
+    /// Decode every packed value in a raw binary file, one row each
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["values", "bits", "hex", "native_hex", "value_file"])]
+    decode_binary: Option<PathBuf>,
+
+    /// Encode every decimal given and write the packed values to a raw binary file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["bits", "hex", "native_hex", "value_file", "decode_binary"])]
+    encode_binary: Option<PathBuf>,
+
+    /// Read the decimal input from a file, for values too long to quote
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["values", "bits", "hex", "native_hex"])]
+    value_file: Option<PathBuf>,
+
+    /// Order the rows of a batch or --decode-binary by quantized value (totalOrder)
+    #[arg(long, value_enum, value_name = "ORDER")]
+    sort: Option<SortOrder>,
+
+    /// Collapse batch inputs that quantize to the same bit pattern, counting each group
+    #[arg(long, conflicts_with_all = ["bits", "hex", "native_hex", "value_file", "decode_binary", "encode_binary"])]
+    dedup: bool,
+
+    /// How to read a single positional: always decimal, always hex bits, or hex
+    /// when it has exactly the format's width in hex digits
+    #[arg(long, value_enum, default_value = "decimal", value_name = "MODE")]
+    interpret: Interpret,
+
+    /// Decimal input; ignored when --bits/--hex are given. Several print one row each
+    #[arg(
+        value_name = "DECIMAL",
+        required_unless_present_any = ["bits", "hex", "native_hex", "value_file", "check_constants", "decode_binary", "sign"]
+    )]
+    values: Vec<String>,
 }
 
-#[derive(Copy, Clone, Debug, ValueEnum)]
-enum RoundingMode {
-    #[value(alias = "nearest", alias = "even")]
-    HalfEven,
-    #[value(alias = "trunc", alias = "zero")]
-    TowardZero,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Report the spacing between adjacent representable values at a magnitude
+    Gap {
+        /// Decimal magnitude to inspect
+        #[arg(value_name = "DECIMAL")]
+        value: String,
+    },
+    /// Add two decimals after quantizing each to the format
+    Add {
+        #[arg(value_name = "A", allow_hyphen_values = true)]
+        a: String,
+        #[arg(value_name = "B", allow_hyphen_values = true)]
+        b: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+        /// Also report the rounding error term e with a + b = s + e exactly
+        #[arg(long)]
+        two_sum: bool,
+    },
+    /// Subtract B from A after quantizing each, reporting any cancellation
+    Sub {
+        #[arg(value_name = "A", allow_hyphen_values = true)]
+        a: String,
+        #[arg(value_name = "B", allow_hyphen_values = true)]
+        b: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Multiply two decimals after quantizing each to the format
+    Mul {
+        #[arg(value_name = "A", allow_hyphen_values = true)]
+        a: String,
+        #[arg(value_name = "B", allow_hyphen_values = true)]
+        b: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+    },
+    /// IEEE 754 remainder: A - n*B with n = A/B rounded to nearest, ties to even
+    Rem {
+        #[arg(value_name = "A", allow_hyphen_values = true)]
+        a: String,
+        #[arg(value_name = "B", allow_hyphen_values = true)]
+        b: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+    },
+    /// C-style fmod: A - n*B with n = A/B truncated toward zero
+    Fmod {
+        #[arg(value_name = "A", allow_hyphen_values = true)]
+        a: String,
+        #[arg(value_name = "B", allow_hyphen_values = true)]
+        b: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Find the nearest representable value with a given property
+    NearestWith {
+        #[arg(value_name = "DECIMAL", allow_hyphen_values = true)]
+        value: String,
+        #[arg(value_name = "PROPERTY", value_enum)]
+        property: Property,
+    },
+    /// Print the exact midpoints to a value's neighbours, where round-to-nearest flips
+    Midpoint {
+        #[arg(value_name = "VALUE", allow_hyphen_values = true)]
+        value: String,
+        /// Read the value as a hexadecimal bit pattern instead of a decimal
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Print the interval of exact inputs that round to a bit pattern
+    Preimage {
+        /// Hexadecimal bit pattern of the target encoding
+        #[arg(value_name = "HEX")]
+        hex: String,
+        /// Also count the decimals with this many places after the point in the interval
+        #[arg(long, value_name = "N")]
+        digits: Option<u32>,
+    },
+    /// Decode a posit<N,ES> pattern into sign, regime, exponent, and fraction
+    Posit {
+        /// Hexadecimal bit pattern of the posit
+        #[arg(value_name = "HEX")]
+        hex: String,
+        /// Total width N of the posit in bits
+        #[arg(long, default_value_t = 16)]
+        nbits: usize,
+        /// Exponent field size ES; useed = 2^(2^ES)
+        #[arg(long, default_value_t = 1)]
+        es: usize,
+    },
+    /// Print the zeros, subnormal and normal extremes, infinities, and NaN of the format
+    Specials,
+    /// Encode values as block floating point with one shared exponent per block
+    Bfp {
+        /// Block layout as block=N,mant=M
+        #[arg(value_name = "LAYOUT")]
+        layout: BfpLayout,
+        #[arg(value_name = "DECIMAL", required = true, allow_hyphen_values = true)]
+        values: Vec<String>,
+    },
+    /// Compare a device result against a reference value and report the ULP distance
+    Compare {
+        #[arg(value_name = "ACTUAL", allow_hyphen_values = true)]
+        actual: String,
+        #[arg(value_name = "EXPECTED", allow_hyphen_values = true)]
+        expected: String,
+        /// Read both operands as hexadecimal bit patterns instead of decimals
+        #[arg(long)]
+        hex: bool,
+        /// Flush subnormal operands to zero of the same sign before comparing
+        #[arg(long)]
+        assume_subnormal_as_zero_in_compare: bool,
+    },
+    /// Check encode/decode stability, monotonicity, and next-value consistency per format
+    #[command(hide = true)]
+    Selftest {
+        /// Formats to check; every built-in preset when omitted
+        #[arg(value_enum)]
+        formats: Vec<FormatChoice>,
+        /// Random bit patterns tried per format on top of the fixed battery
+        #[arg(long, default_value_t = 2000)]
+        samples: usize,
+    },
+    /// Print a shell completion script
+    #[command(hide = true)]
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print a roff man page
+    #[command(hide = true)]
+    Manpage,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
-enum Notation {
-    Plain,
-    Scientific,
+enum OutputFormat {
+    Text,
+    Json,
+    /// A Verilog/SystemVerilog `localparam` holding the bit pattern
+    Verilog,
+    /// A C snippet that reproduces the value with `memcpy`
+    C,
+    /// A Python one-liner that reproduces the value with `struct` (or numpy for FP16)
+    Python,
 }
 
-#[derive(Debug, Clone)]
-struct FloatSpec {
-    name: &'static str,
-    exponent_bits: usize,
-    significand_bits: usize,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Interpret {
+    Decimal,
+    Hex,
+    /// Hex when the input is a full-width hex encoding, otherwise decimal
+    Auto,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Class {
-    Normal,
-    Subnormal,
-    Zero,
-    PosInfinity,
-    NegInfinity,
-    Nan,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum SortOrder {
+    /// Smallest first, by IEEE 754 totalOrder
+    Asc,
+    /// Largest first
+    Desc,
 }
 
-#[derive(Debug, Clone)]
-struct SoftFloat {
-    class: Class,
-    sign: bool,
-    exponent: i32,        // unbiased exponent for Normal/Subnormal; min exp for zero
-    significand: BigUint, // stored fraction bits (no implicit leading 1)
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum RangeScale {
+    /// Fraction of the largest finite value
+    Linear,
+    /// Position of log2|x| between the smallest subnormal and the largest finite value
+    Log,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     let spec = resolve_format(&cli)?;
+    let target = resolve_target_format(&cli)?;
+    if cli.strict_ieee {
+        check_strict_ieee(&spec)?;
+        if let Some(target) = &target {
+            check_strict_ieee(target)?;
+        }
+    }
+    let display = display_options(&cli)?;
+
+    if let Some(command) = &cli.command {
+        return run_command(
+            command,
+            &spec,
+            cli.rounding,
+            &display,
+            cli.normalize_minus_zero,
+        );
+    }
+
+    if let Some(path) = &cli.decode_binary {
+        let bytes = fs::read(path)
+            .with_context(|| format!("unable to read --decode-binary {}", path.display()))?;
+        let mut rows = decode_packed(&bytes, &spec, cli.endianness, cli.field_order)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, soft)| Ok((total_order_key(&soft, &spec)?, (i, soft))))
+            .collect::<Result<Vec<_>>>()?;
+        sort_rows(&mut rows, cli.sort);
+        for (_, (i, soft)) in &rows {
+            println!(
+                "Value {i:<6}: {}",
+                describe_softfloat(soft, &spec, &display)?
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &cli.encode_binary {
+        let values = cli
+            .values
+            .iter()
+            .map(|raw| {
+                let parsed = parse_decimal_with(raw, display.decimal_separator)?;
+                Ok(parsed_to_softfloat(&parsed, &spec, cli.rounding))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let bytes = encode_packed(&values, &spec, cli.endianness, cli.field_order)?;
+        fs::write(path, &bytes)
+            .with_context(|| format!("unable to write --encode-binary {}", path.display()))?;
+        println!(
+            "Wrote {} {} values ({} bytes) to {}",
+            values.len(),
+            spec.name,
+            bytes.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+    if cli.dedup {
+        let rows = batch_rows(&cli, &spec, &display)?;
+        let groups = dedup_rows(rows, &spec)?;
+        for (i, (soft, inputs)) in groups.iter().enumerate() {
+            println!(
+                "Value {i:<6}: {} <- {} input{} ({})",
+                describe_softfloat(soft, &spec, &display)?,
+                inputs.len(),
+                if inputs.len() == 1 { "" } else { "s" },
+                inputs.join(", ")
+            );
+        }
+        println!(
+            "Distinct    : {} of {} inputs",
+            groups.len(),
+            cli.values.len()
+        );
+        return Ok(());
+    }
+    if cli.values.len() > 1 || cli.summary {
+        let rows = batch_rows(&cli, &spec, &display)?;
+        for (i, raw, soft) in &rows {
+            println!(
+                "Input {i:<6}: {raw} -> {}",
+                describe_softfloat(soft, &spec, &display)?
+            );
+        }
+        if cli.summary {
+            print!("{}", batch_summary(&rows, &spec, &display)?);
+        }
+        return Ok(());
+    }
+
+    if cli.check_constants {
+        for (name, soft) in reference_constants(&spec, cli.rounding) {
+            let hex = display
+                .hex_case
+                .apply(&bits_to_hex(&softfloat_to_bits(&soft, &spec)?));
+            let value = softfloat_to_rational(&soft, &spec)
+                .map(|v| format_rational(&v, &display))
+                .unwrap_or_else(|| format!("{:?}", soft.class));
+            println!("{name:<12}: {hex}  {value}");
+        }
+        return Ok(());
+    }
+
+    if cli.complex {
+        let raw = match &cli.value_file {
+            Some(path) => read_value_file(path)?,
+            None => cli.values.first().cloned().unwrap_or_default(),
+        };
+        let (real, imag) = parse_complex(&delocalize_decimal(&raw, display.decimal_separator)?)?;
+        let quantized = quantize_complex(&real, &imag, &spec, cli.rounding);
+        println!("Format      : {}", spec.name);
+        println!(
+            "Real        : {}",
+            describe_softfloat(&quantized.real, &spec, &display)?
+        );
+        println!(
+            "Imag        : {}",
+            describe_softfloat(&quantized.imag, &spec, &display)?
+        );
+        match &quantized.error_squared {
+            Some(squared) => {
+                println!("Error |e|^2 : {}", format_rational(squared, &display));
+                let magnitude = squared.to_f64().unwrap_or(f64::INFINITY).sqrt();
+                println!("Error |e|   : ~{magnitude:e}");
+            }
+            None => println!("Error |e|   : (undefined for NaN/Infinity)"),
+        }
+        let mut exceptions = conversion_exceptions(&real, &quantized.real, &spec);
+        for flag in conversion_exceptions(&imag, &quantized.imag, &spec) {
+            if !exceptions.contains(&flag) {
+                exceptions.push(flag);
+            }
+        }
+        return check_traps(&exceptions, &cli.trap);
+    }
 
     let input_kind = if let Some(bits) = cli.bits.as_deref() {
         Input::Bits(bits.to_string())
     } else if let Some(hex) = cli.hex.as_deref() {
-        Input::Hex(hex.to_string())
+        Input::Hex(hex.to_string(), cli.endianness)
+    } else if let Some(hex) = cli.native_hex.as_deref() {
+        Input::Hex(hex.to_string(), Endianness::Little)
+    } else if let (Some(sign), Some(exp), Some(mant)) = (&cli.sign, &cli.exp_field, &cli.mant_field)
+    {
+        Input::Fields(sign.clone(), exp.clone(), mant.clone())
+    } else if let Some(path) = &cli.value_file {
+        Input::Decimal(read_value_file(path)?)
     } else {
         let raw = cli
-            .value
-            .clone()
+            .values
+            .first()
+            .cloned()
             .expect("positional argument enforced by clap");
-        Input::Decimal(raw)
+        positional_input(raw, &cli, &spec)?
     };
 
     let mut source_rational: Option<BigRational> = None;
+    let mut parsed_input: Option<ParsedValue> = None;
+    let mut exact_integer: Option<bool> = None;
+    let mut native_comparison: Option<NativeComparison> = None;
     let soft = match input_kind {
-        Input::Bits(b) => bits_to_softfloat(&b, &spec)?,
-        Input::Hex(h) => {
-            let bits = hex_to_bits(&h, total_bits(&spec)?)?;
+        Input::Bits(b) => bits_to_softfloat(&cli.field_order.unpack(&b, &spec)?, &spec)?,
+        Input::Fields(sign, exp, mant) => {
+            let bits = assemble_fields(
+                &parse_field_value(&sign)?,
+                &parse_field_value(&exp)?,
+                &parse_field_value(&mant)?,
+                &spec,
+            )?;
             bits_to_softfloat(&bits, &spec)?
         }
+        Input::Hex(h, endianness) => {
+            let total = total_bits(&spec)?;
+            let h = match endianness {
+                Endianness::Big => h,
+                Endianness::Little => swap_hex_bytes(&h, total)?,
+            };
+            let bits = hex_to_bits(&h, total)?;
+            bits_to_softfloat(&cli.field_order.unpack(&bits, &spec)?, &spec)?
+        }
         Input::Decimal(ref d) => {
+            let d = &delocalize_decimal(d, display.decimal_separator)?;
+            if cli.integer {
+                let n = BigInt::from_str(d.trim())
+                    .with_context(|| format!("unable to parse integer input: {d}"))?;
+                exact_integer = Some(integer_is_exact(&n, &spec));
+            }
             let parsed = parse_decimal(d)?;
+            if cli.compare_native {
+                native_comparison = Some(compare_native(d, &spec, cli.rounding)?);
+            }
             if let ParsedValue::Finite(ref v) = parsed {
                 source_rational = Some(v.clone());
             }
-            parsed_to_softfloat(&parsed, &spec, cli.rounding)
+            let soft = match cli.native {
+                Some(native) => bits_to_softfloat(&native_bits(d, native, &spec)?, &spec)?,
+                None => match cli.round_to_bits {
+                    Some(k) => round_to_bits(&parsed, &spec, k, cli.rounding)?,
+                    None if cli.require_exact => parsed_to_softfloat_exact(&parsed, &spec)
+                        .with_context(|| format!("--require-exact: {d} would be rounded"))?,
+                    None => parsed_to_softfloat(&parsed, &spec, cli.rounding),
+                },
+            };
+            parsed_input = Some(parsed);
+            soft
         }
     };
+    let soft = if cli.canonical_nan && soft.class == Class::Nan {
+        canonical_nan(&spec)
+    } else {
+        soft
+    };
 
-    let stored_value = softfloat_to_rational(&soft, &spec);
-    let bits = softfloat_to_bits(&soft, &spec);
-    let hex = bits_to_hex(&bits);
-
-    println!("Format      : {}", spec.name);
-    println!(
-        "Layout      : 1 sign | {} exponent | {} significand",
-        spec.exponent_bits, spec.significand_bits
-    );
-    println!("Class       : {:?}", soft.class);
-    println!("Sign        : {}", if soft.sign { "-" } else { "+" });
-    println!("Exponent    : {}", soft.exponent);
-    println!("Binary      : {}", bits);
-    println!("Hex         : {}", hex);
-
-    if let Some(val) = stored_value {
-        println!(
-            "Stored      : {}",
-            format_rational(&val, cli.precision, cli.notation)
-        );
-        if let Some(src) = source_rational {
-            let err = &val - &src;
-            println!(
-                "Error       : {}",
-                format_rational(&err, cli.precision, cli.notation)
-            );
+    let stored_value = match cli.significand_scale.as_deref() {
+        Some(raw) => {
+            let scale = match parse_decimal_with(raw, display.decimal_separator)? {
+                ParsedValue::Finite(v) => v,
+                _ => bail!("--significand-scale must be a finite decimal"),
+            };
+            softfloat_to_rational_scaled(&soft, &spec, &scale)
         }
-    } else {
-        println!("Stored      : {:?}", soft.class);
-        if source_rational.is_some() {
-            println!("Error       : (undefined for NaN/Infinity)");
+        None => softfloat_to_rational(&soft, &spec),
+    };
+    if cli.reformat {
+        match &stored_value {
+            Some(val) => println!("{}", format_rational(val, &display)),
+            None => println!("{:?}", soft.class),
         }
+        return Ok(());
     }
 
-    Ok(())
-}
+    let mut report = ConvertReport::new(
+        &soft,
+        &spec,
+        stored_value.as_ref(),
+        source_rational.as_ref(),
+        cli.rounding,
+        &display,
+    )?;
+    let standard_bits = report.bits.clone();
+    report.bits = cli.field_order.pack(&standard_bits, &spec);
+    report.hex = display.hex_case.apply(&bits_to_hex(&report.bits));
+
+    let exceptions = match &parsed_input {
+        Some(parsed) => conversion_exceptions(parsed, &soft, &spec),
+        None => Vec::new(),
+    };
 
-#[derive(Debug)]
-enum Input {
-    Decimal(String),
-    Bits(String),
-    Hex(String),
-}
+    let rendered = match cli.output {
+        OutputFormat::Text => None,
+        OutputFormat::Json => Some(render_json(&report, cli.json_pretty)?),
+        OutputFormat::Verilog => Some(verilog_localparam(
+            &standard_bits,
+            &spec,
+            cli.field_order,
+            display.hex_case,
+        )),
+        OutputFormat::C => Some(c_snippet(&report.bits, &spec, display.hex_case)),
+        OutputFormat::Python => Some(python_snippet(&report.bits, &spec, display.hex_case)),
+    };
+    if let Some(rendered) = rendered {
+        println!("{rendered}");
+        return check_traps(&exceptions, &cli.trap);
+    }
 
-#[derive(Debug, Clone)]
-enum ParsedValue {
-    Finite(BigRational),
-    PosInfinity,
-    NegInfinity,
-    Nan,
-}
+    let mut text = TextReport::new(cli.report_width);
+    text.row("Format", report.format);
+    let layout: Vec<String> = cli
+        .field_order
+        .0
+        .iter()
+        .map(|field| match field {
+            Field::Sign => "1 sign".to_string(),
+            Field::Exponent => format!("{} exponent", report.exponent_bits),
+            Field::Significand if spec.explicit_integer_bit => {
+                format!("1 integer | {} significand", report.significand_bits)
+            }
+            Field::Significand => format!("{} significand", report.significand_bits),
+        })
+        .collect();
+    text.row("Layout", layout.join(" | "));
+    text.row("Class", &report.class);
+    text.row("Sign", report.sign);
+    if display.plus_sign {
+        text.row("Exponent", format!("{:+}", report.exponent.unbiased));
+    } else {
+        text.row("Exponent", report.exponent.unbiased);
+    }
+    text.row("Binary", &report.bits);
+    if cli.hex_bytes {
+        text.row(
+            "Hex",
+            display.hex_case.apply(&bits_to_hex_bytes(&report.bits)),
+        );
+    } else {
+        text.row("Hex", &report.hex);
+    }
+    text.row("Mantissa (dec)", &report.mantissa);
+    if cli.monotone_key {
+        let key = bits_to_hex(&monotone_key(&soft, &spec)?);
+        text.row("Monotone key", display.hex_case.apply(&key));
+    }
 
-fn resolve_format(cli: &Cli) -> Result<FloatSpec> {
-    let spec = match cli.format {
-        FormatChoice::Fp16 => FloatSpec {
-            name: "FP16",
-            exponent_bits: 5,
-            significand_bits: 10,
-        },
-        FormatChoice::Bfloat16 => FloatSpec {
-            name: "bfloat16",
-            exponent_bits: 8,
-            significand_bits: 7,
-        },
-        FormatChoice::Fp32 => FloatSpec {
-            name: "FP32",
-            exponent_bits: 8,
-            significand_bits: 23,
-        },
-        FormatChoice::Fp64 => FloatSpec {
-            name: "FP64",
-            exponent_bits: 11,
-            significand_bits: 52,
-        },
-        FormatChoice::Tf32 => FloatSpec {
-            name: "TensorFloat-32",
-            exponent_bits: 8,
-            significand_bits: 10,
-        },
-        FormatChoice::Custom => {
-            let e = cli
-                .exponent_bits
-                .ok_or_else(|| anyhow!("--exp is required for --format=custom"))?;
-            let s = cli
-                .significand_bits
-                .ok_or_else(|| anyhow!("--mant is required for --format=custom"))?;
-            if !(2..=11).contains(&e) {
-                bail!("exponent bits must be between 2 and 11");
+    if cli.show_input_rational {
+        match &source_rational {
+            Some(src) => text.row("Input rational", format_fraction(src)),
+            None => text.row("Input rational", "(not finite)"),
+        }
+    }
+
+    if let Some(val) = &stored_value {
+        let compact = match soft.class {
+            Class::Subnormal if cli.trim_denormal_trailing => integer_times_pow2(val),
+            _ => None,
+        };
+        match compact {
+            Some(compact) => text.row("Stored", compact),
+            None => text.row("Stored", format_rational(val, &display)),
+        }
+        if let (Some(src), Some(error)) = (&source_rational, &report.error) {
+            text.row("Error", error);
+            if let Some(halfway) = report.exactly_halfway {
+                text.row("Exactly halfway", if halfway { "yes" } else { "no" });
             }
-            if !(1..=52).contains(&s) {
-                bail!("significand bits must be between 1 and 52");
+            if let Some(carry) = report.rounding_carry {
+                text.row("Rounding carry", if carry { "yes" } else { "no" });
             }
-            FloatSpec {
-                name: "Custom",
-                exponent_bits: e,
-                significand_bits: s,
+            if cli.error_ulps {
+                let (whole, remainder, total) = error_in_ulps(&(val - src), val, &spec);
+                text.row(
+                    "Error ULPs",
+                    format!(
+                        "{whole} ULP {} {} ({} ULP)",
+                        if remainder.is_negative() { "-" } else { "+" },
+                        format_rational(&remainder.abs(), &display),
+                        format_rational(&total, &display)
+                    ),
+                );
             }
         }
-    };
-    Ok(spec)
-}
+    } else {
+        text.row("Stored", &report.class);
+        if source_rational.is_some() {
+            text.row("Error", "(undefined for NaN/Infinity)");
+        }
+    }
 
-fn total_bits(spec: &FloatSpec) -> Result<usize> {
-    Ok(1 + spec.exponent_bits + spec.significand_bits)
-}
+    if let Some(raw) = cli.relative_to.as_deref() {
+        let baseline = match parse_decimal_with(raw, display.decimal_separator)? {
+            ParsedValue::Finite(v) => v,
+            _ => bail!("--relative-to must be a finite decimal"),
+        };
+        match &stored_value {
+            Some(val) => {
+                let (diff, ulps) = baseline_difference(val, &baseline, &spec);
+                text.row(
+                    "Vs baseline",
+                    format!(
+                        "{} ({} ULP)",
+                        format_rational(&diff, &display),
+                        format_rational(&ulps, &display)
+                    ),
+                );
+            }
+            None => text.row("Vs baseline", "(undefined for NaN/Infinity)"),
+        }
+    }
 
-fn parse_decimal(raw: &str) -> Result<ParsedValue> {
-    let lower = raw.trim().to_ascii_lowercase();
-    match lower.as_str() {
-        "inf" | "+inf" | "infinity" => Ok(ParsedValue::PosInfinity),
-        "-inf" | "-infinity" => Ok(ParsedValue::NegInfinity),
-        "nan" => Ok(ParsedValue::Nan),
-        _ => {
-            let dec = BigDecimal::from_str(raw)
-                .with_context(|| format!("unable to parse decimal input: {raw}"))?;
-            let (int, exp) = dec.into_bigint_and_exponent();
-            let rat = if exp >= 0 {
-                let scale = BigInt::from(10u32).pow(exp as u32);
-                BigRational::new(int, scale)
-            } else {
-                let scale = BigInt::from(10u32).pow((-exp) as u32);
-                BigRational::new(int * scale, BigInt::one())
-            };
-            Ok(ParsedValue::Finite(rat))
+    if let Some(radius) = cli.ulp_grid {
+        match ulp_grid(&soft, radius, &spec)? {
+            Some(grid) => {
+                text.row("ULP grid", "");
+                for (offset, sf) in grid {
+                    text.detail(format!(
+                        "  {offset:<+6} {}",
+                        describe_softfloat(&sf, &spec, &display)?
+                    ));
+                }
+            }
+            None => text.row("ULP grid", "(undefined for NaN)"),
         }
     }
-}
 
-fn parsed_to_softfloat(value: &ParsedValue, spec: &FloatSpec, rounding: RoundingMode) -> SoftFloat {
-    let sign = match value {
-        ParsedValue::Finite(v) => v.is_negative(),
-        ParsedValue::PosInfinity => false,
-        ParsedValue::NegInfinity => true,
-        ParsedValue::Nan => false,
-    };
+    if cli.significand_hex {
+        match significand_hex(&soft, &spec) {
+            Some(full) => text.row(
+                "Sig. hex",
+                format!(
+                    "{} (stored fraction {})",
+                    display.hex_case.apply(&full),
+                    display.hex_case.apply(&format!("0x{:X}", soft.significand))
+                ),
+            ),
+            None => text.row("Sig. hex", "(undefined for NaN/Infinity)"),
+        }
+    }
 
-    match value {
-        ParsedValue::Nan => SoftFloat {
-            class: Class::Nan,
-            sign: false,
-            exponent: 0,
-            significand: BigUint::zero(),
-        },
-        ParsedValue::PosInfinity => SoftFloat {
-            class: Class::PosInfinity,
-            sign: false,
-            exponent: max_exponent(spec) + 1,
-            significand: BigUint::zero(),
-        },
-        ParsedValue::NegInfinity => SoftFloat {
-            class: Class::NegInfinity,
-            sign: true,
-            exponent: max_exponent(spec) + 1,
-            significand: BigUint::zero(),
-        },
-        ParsedValue::Finite(v) if v.is_zero() => SoftFloat {
-            class: Class::Zero,
-            sign,
-            exponent: min_exponent(spec),
-            significand: BigUint::zero(),
-        },
-        ParsedValue::Finite(v) => {
-            let abs = v.abs();
-            let bias = bias(spec);
-            let max_exp = bias as i32;
-            let min_norm = 1 - bias as i32;
-
-            let exp = log2_floor(&abs);
-
-            if exp > max_exp {
-                return SoftFloat {
-                    class: if sign {
-                        Class::NegInfinity
-                    } else {
-                        Class::PosInfinity
-                    },
-                    sign,
-                    exponent: max_exp + 1,
-                    significand: BigUint::zero(),
-                };
-            }
+    if cli.trailing_zero_bits {
+        match trailing_zero_bits(&soft, &spec) {
+            Some(k) => text.row(
+                "Trailing zeros",
+                format!("{k} of {} fraction bits", spec.significand_bits),
+            ),
+            None => text.row("Trailing zeros", "(undefined for NaN/Infinity)"),
+        }
+    }
 
-            if exp >= min_norm {
-                quantize_normal(&abs, sign, exp, spec, rounding)
-            } else {
-                quantize_subnormal(&abs, sign, spec, rounding)
+    if cli.show_ulp {
+        match &stored_value {
+            Some(val) => {
+                let ulp = format_rational(&ulp_gap(&val.abs(), &spec), &display);
+                match soft.class {
+                    Class::Zero | Class::Subnormal => {
+                        text.row("ULP value", format!("{ulp} (subnormal spacing)"))
+                    }
+                    _ => text.row("ULP value", ulp),
+                }
             }
+            None => text.row("ULP value", "(undefined for NaN/Infinity)"),
         }
     }
-}
 
-fn bias(spec: &FloatSpec) -> i32 {
-    (1i32 << (spec.exponent_bits - 1)) - 1
-}
+    if cli.show_implicit_bit {
+        let repr =
+            implicit_significand(&soft, &spec).unwrap_or_else(|| format!("{:?}", soft.class));
+        text.row("Significand", repr);
+    }
 
-fn min_exponent(spec: &FloatSpec) -> i32 {
-    1 - bias(spec)
-}
+    if cli.binary_sci {
+        let repr = binary_scientific(&soft, &spec).unwrap_or_else(|| format!("{:?}", soft.class));
+        text.row("Binary sci", repr);
+    }
 
-fn log2_floor(r: &BigRational) -> i32 {
-    let num_bits = r.numer().bits() as i32;
-    let den_bits = r.denom().bits() as i32;
-    let mut exp = num_bits - den_bits - 1;
+    if cli.continued_fraction {
+        let repr = match &stored_value {
+            Some(val) => format_continued_fraction(&continued_fraction(val)),
+            None => format!("{:?}", soft.class),
+        };
+        text.row("Cont. frac", repr);
+    }
 
-    loop {
-        let cmp_low = compare_pow2(r, exp);
-        let cmp_high = compare_pow2(r, exp + 1);
-        if (cmp_low != Ordering::Less) && cmp_high == Ordering::Less {
-            return exp;
-        }
-        if cmp_low == Ordering::Less {
-            exp -= 1;
-        } else {
-            exp += 1;
+    if cli.show_all_roundings
+        && let Some(parsed) = &parsed_input
+    {
+        text.row("Roundings", "");
+        for (mode, sf) in all_roundings(parsed, &spec) {
+            let name = mode
+                .to_possible_value()
+                .map(|v| v.get_name().to_string())
+                .unwrap_or_default();
+            let hex = display
+                .hex_case
+                .apply(&bits_to_hex(&softfloat_to_bits(&sf, &spec)?));
+            let error = match (softfloat_to_rational(&sf, &spec), &source_rational) {
+                (Some(val), Some(src)) => format_rational(&(val - src), &display),
+                _ => "(undefined for NaN/Infinity)".to_string(),
+            };
+            text.detail(format!("  {name:<12} 0x{hex:<10} error {error}"));
         }
     }
-}
 
-fn compare_pow2(r: &BigRational, exp: i32) -> Ordering {
-    let pow = BigInt::one() << exp.abs();
-    if exp >= 0 {
-        r.numer().cmp(&(r.denom() * pow))
-    } else {
-        (r.numer() * pow).cmp(r.denom())
+    if cli.count_exact_digits {
+        match stored_value.as_ref().and_then(exact_decimal_digits) {
+            Some(digits) => text.row("Exact digits", digits),
+            None => text.row("Exact digits", "N/A"),
+        }
     }
-}
 
-fn quantize_normal(
-    abs: &BigRational,
-    sign: bool,
-    exp: i32,
-    spec: &FloatSpec,
-    rounding: RoundingMode,
-) -> SoftFloat {
-    let frac = abs / pow2(exp);
-    // frac should be in [1, 2)
-    let mant = &frac - BigRational::one();
-    let needed = spec.significand_bits + 3;
-    let (bits, sticky) = fraction_bits(&mant, needed);
-    let (mantissa, carry) = round_bits(bits, sticky, spec.significand_bits, rounding);
-
-    let mut exponent = exp;
-    let mut significand = mantissa;
-
-    if carry {
-        exponent += 1;
-        significand = BigUint::zero();
-    }
-
-    let max_exp = bias(spec) as i32;
-    if exponent > max_exp {
-        return SoftFloat {
-            class: if sign {
-                Class::NegInfinity
+    if cli.check_bf16_shortcut
+        && let Some(parsed) = &parsed_input
+    {
+        let fp32 = preset_spec(FormatChoice::Fp32).expect("FP32 preset");
+        let bf16 = preset_spec(FormatChoice::Bfloat16).expect("BF16 preset");
+        let (direct, via) = double_rounding(parsed, &fp32, &bf16, cli.rounding);
+        let matches = softfloat_to_bits(&direct, &bf16)? == softfloat_to_bits(&via, &bf16)?;
+        text.row("BF16 direct", describe_softfloat(&direct, &bf16, &display)?);
+        text.row("BF16 via F32", describe_softfloat(&via, &bf16, &display)?);
+        text.row(
+            "Shortcut",
+            if matches {
+                "matches"
             } else {
-                Class::PosInfinity
+                "diverges (double rounding)"
             },
-            sign,
-            exponent,
-            significand: BigUint::zero(),
+        );
+    }
+
+    if let Some(comparison) = &native_comparison {
+        let native_hex = display
+            .hex_case
+            .apply(&bits_to_hex(&softfloat_to_bits(&comparison.native, &spec)?));
+        let verdict = match &comparison.ulps {
+            Some(ulps) if ulps.is_zero() => "agrees".to_string(),
+            Some(ulps) => format!("differs by {ulps} ULP"),
+            None if comparison.engine.class == comparison.native.class => {
+                "agrees (NaN)".to_string()
+            }
+            None => "differs (NaN)".to_string(),
         };
+        text.row("Native", format!("{native_hex} ({verdict})"));
     }
 
-    SoftFloat {
-        class: Class::Normal,
-        sign,
-        exponent,
-        significand,
+    if let Some(target) = &target {
+        let (converted, diff) = diff_formats(&soft, &spec, target, cli.rounding);
+        text.row(
+            "Target",
+            format!(
+                "{} (1 sign | {} exponent | {} significand)",
+                target.name, target.exponent_bits, target.significand_bits
+            ),
+        );
+        text.row(
+            "Converted",
+            describe_softfloat(&converted, target, &display)?,
+        );
+        text.row(
+            "Exp bits",
+            format!(
+                "{} -> {} ({})",
+                spec.exponent_bits,
+                target.exponent_bits,
+                describe_bit_delta(diff.exponent_bits_delta, "range")
+            ),
+        );
+        text.row(
+            "Mant bits",
+            format!(
+                "{} -> {} ({})",
+                spec.significand_bits,
+                target.significand_bits,
+                describe_bit_delta(diff.significand_bits_delta, "precision")
+            ),
+        );
+        text.row("Inexact", if diff.inexact { "yes" } else { "no" });
     }
-}
 
-fn quantize_subnormal(
-    abs: &BigRational,
-    sign: bool,
-    spec: &FloatSpec,
-    rounding: RoundingMode,
-) -> SoftFloat {
-    let min_exp = min_exponent(spec);
-    let scaled = abs / pow2(min_exp);
-    let needed = spec.significand_bits + 3;
-    let (bits, sticky) = fraction_bits(&scaled, needed);
-    let (mantissa, carry) = round_bits(bits, sticky, spec.significand_bits, rounding);
-
-    if carry {
-        // Rounded up into the normal range at the smallest exponent.
-        return SoftFloat {
-            class: Class::Normal,
-            sign,
-            exponent: min_exp,
-            significand: BigUint::zero(),
-        };
+    if let Some(exact) = exact_integer {
+        text.row("Exact", if exact { "yes" } else { "no" });
     }
 
-    let class = if mantissa.is_zero() {
-        Class::Zero
-    } else {
-        Class::Subnormal
-    };
+    if cli.show_range {
+        match source_rational.as_ref().or(stored_value.as_ref()) {
+            Some(v) => {
+                let abs = v.abs();
+                match cli.range_scale {
+                    RangeScale::Linear => text.row(
+                        "Range",
+                        format!(
+                            "{}% of max finite",
+                            format_rational(
+                                &(range_fraction(&abs, &spec) * BigInt::from(100)),
+                                &display
+                            )
+                        ),
+                    ),
+                    RangeScale::Log => text.row(
+                        "Range",
+                        format!(
+                            "{:.4}% of log2 range",
+                            log_range_fraction(&abs, &spec) * 100.0
+                        ),
+                    ),
+                }
+            }
+            None => text.row("Range", "(undefined for NaN/Infinity)"),
+        }
+    }
+
+    if cli.since_one_ulp {
+        match ulps_from_one(&soft, &spec)? {
+            Some(ulps) => text.row("ULPs from 1", format!("{ulps:+}")),
+            None => text.row("ULPs from 1", "(undefined)"),
+        }
+    }
 
-    SoftFloat {
-        class,
-        sign,
-        exponent: min_exp,
-        significand: mantissa,
+    if cli.min_format {
+        match source_rational.as_ref().or(stored_value.as_ref()) {
+            Some(value) => match min_format(value) {
+                Some(min) => text.row(
+                    "Min format",
+                    format!(
+                        "{} significand bits ({} fraction), {} exponent bits",
+                        min.significant_bits,
+                        min.significant_bits - 1,
+                        min.exponent_bits
+                    ),
+                ),
+                None => text.row("Min format", "not exactly representable (not dyadic)"),
+            },
+            None => text.row("Min format", "(undefined for NaN/Infinity)"),
+        }
     }
+
+    if cli.exponent_range {
+        let range = exponent_range(&spec);
+        text.row("Max exp", range.max_normal);
+        text.row("Min exp", range.min_normal);
+        text.row("Min sub exp", range.min_subnormal);
+    }
+
+    if cli.underflow_boundary {
+        match underflow_boundary(&spec) {
+            Some(boundary) => {
+                let range = exponent_range(&spec);
+                text.row(
+                    "Max subnorm",
+                    format_rational(&boundary.max_subnormal, &display),
+                );
+                text.row(
+                    "Min normal",
+                    format!(
+                        "{} (2^{})",
+                        format_rational(&boundary.min_normal, &display),
+                        range.min_normal
+                    ),
+                );
+                text.row(
+                    "Subnorm ULP",
+                    format!(
+                        "{} (2^{})",
+                        format_rational(&boundary.subnormal_ulp, &display),
+                        range.min_subnormal
+                    ),
+                );
+            }
+            None => text.row(
+                "Underflow",
+                format!("(no subnormal/normal boundary in {})", spec.name),
+            ),
+        }
+    }
+
+    print!("{text}");
+    check_traps(&exceptions, &cli.trap)
+}
+
+/// The file's contents with surrounding whitespace (including the final
+/// newline most editors add) removed.
+fn read_value_file(path: &Path) -> Result<String> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("unable to read --value-file {}", path.display()))?;
+    Ok(contents.trim().to_string())
 }
 
-fn pow2(exp: i32) -> BigRational {
-    if exp >= 0 {
-        BigRational::from_integer(BigInt::one() << exp)
+fn render_json(report: &ConvertReport, pretty: bool) -> Result<String> {
+    Ok(if pretty {
+        serde_json::to_string_pretty(report)?
     } else {
-        BigRational::new(BigInt::one(), BigInt::one() << (-exp))
-    }
+        serde_json::to_string(report)?
+    })
 }
 
-fn fraction_bits(frac: &BigRational, bits: usize) -> (Vec<u8>, bool) {
-    let mut result = Vec::with_capacity(bits);
-    let mut remainder = frac.clone();
-    let two = BigInt::from(2);
-    for _ in 0..bits {
-        let doubled = &remainder * &two;
-        if doubled >= BigRational::one() {
-            result.push(1);
-            remainder = doubled - BigRational::one();
-        } else {
-            result.push(0);
-            remainder = doubled;
-        }
+/// Fails after the report is printed so CI logs still show what was converted.
+fn check_traps(raised: &[Exception], traps: &[Exception]) -> Result<()> {
+    let trapped: Vec<String> = raised
+        .iter()
+        .filter(|e| traps.contains(e))
+        .map(|e| format!("{e:?}").to_lowercase())
+        .collect();
+    if !trapped.is_empty() {
+        bail!("trapped floating-point exception: {}", trapped.join(", "));
     }
-    let sticky = !remainder.is_zero();
-    (result, sticky)
+    Ok(())
 }
 
-fn round_bits(bits: Vec<u8>, sticky: bool, width: usize, mode: RoundingMode) -> (BigUint, bool) {
-    let kept = &bits[..width];
-    let kept_value = bits_to_uint(kept);
+/// An operand of the arithmetic subcommands: a decimal quantized to the
+/// format, or with `--hex` an encoding taken as-is (so NaN payloads survive).
+fn arithmetic_operand(
+    raw: &str,
+    hex: bool,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+    display: &DisplayOptions,
+) -> Result<SoftFloat> {
+    if hex {
+        bits_to_softfloat(&hex_to_bits(raw, total_bits(spec)?)?, spec)
+    } else {
+        decimal_operand(raw, spec, rounding, display)
+    }
+}
 
-    match mode {
-        RoundingMode::TowardZero => (kept_value, false),
-        RoundingMode::HalfEven => {
-            if width >= bits.len() {
-                return (kept_value, false);
+fn run_command(
+    command: &Command,
+    spec: &FloatSpec,
+    rounding: RoundingMode,
+    display: &DisplayOptions,
+    normalize_zero: bool,
+) -> Result<()> {
+    let result = |sf: SoftFloat| {
+        if normalize_zero {
+            normalize_minus_zero(sf)
+        } else {
+            sf
+        }
+    };
+    match command {
+        Command::Gap { value } => {
+            let abs = match parse_decimal_with(value, display.decimal_separator)? {
+                ParsedValue::Finite(v) => v.abs(),
+                _ => bail!("gap requires a finite decimal magnitude"),
+            };
+            let exp = binade_exponent(&abs, spec);
+            let region = if compare_pow2(&abs, min_exponent(spec)) != Ordering::Less {
+                "normal"
+            } else {
+                "subnormal"
+            };
+            let gap_exp = exp - spec.significand_bits as i32;
+            println!("Format      : {}", spec.name);
+            println!("Binade      : 2^{exp} ({region})");
+            println!(
+                "Gap         : {} (2^{gap_exp})",
+                format_rational(&ulp_gap(&abs, spec), display)
+            );
+        }
+        Command::Add { a, b, hex, two_sum } => {
+            let a = arithmetic_operand(a, *hex, spec, rounding, display)?;
+            let b = arithmetic_operand(b, *hex, spec, rounding, display)?;
+            let (sum, err) = two_sum_softfloats(&a, &b, spec, rounding);
+            let sum = result(sum);
+            println!("Format      : {}", spec.name);
+            println!("A           : {}", describe_softfloat(&a, spec, display)?);
+            println!("B           : {}", describe_softfloat(&b, spec, display)?);
+            println!("Sum         : {}", describe_softfloat(&sum, spec, display)?);
+            if *two_sum {
+                match err {
+                    Some((e, exact)) => println!(
+                        "Two-sum err : {}{}",
+                        describe_softfloat(&e, spec, display)?,
+                        if exact { "" } else { " (inexact)" }
+                    ),
+                    None => println!("Two-sum err : (undefined for NaN/Infinity)"),
+                }
             }
-            let guard = bits.get(width).copied().unwrap_or(0);
-            let round_bit = bits.get(width + 1).copied().unwrap_or(0);
-            let rest_sticky = sticky || bits.iter().skip(width + 2).any(|b| *b == 1);
-
-            let should_increment = match (guard, round_bit, rest_sticky) {
-                (1, 0, false) => kept.last().copied().unwrap_or(0) == 1,
-                (1, _, _) => true,
-                _ => false,
+        }
+        Command::Sub { a, b, hex } => {
+            let a = arithmetic_operand(a, *hex, spec, rounding, display)?;
+            let b = arithmetic_operand(b, *hex, spec, rounding, display)?;
+            let negated = SoftFloat {
+                sign: !b.sign,
+                ..b.clone()
             };
-
-            if should_increment {
-                let max_val = (BigUint::one() << width) - BigUint::one();
-                if kept_value == max_val {
-                    (BigUint::zero(), true)
+            let (difference, _) = two_sum_softfloats(&a, &negated, spec, rounding);
+            let difference = result(difference);
+            println!("Format      : {}", spec.name);
+            println!("A           : {}", describe_softfloat(&a, spec, display)?);
+            println!("B           : {}", describe_softfloat(&b, spec, display)?);
+            println!(
+                "Difference  : {}",
+                describe_softfloat(&difference, spec, display)?
+            );
+            match subtraction_cancellation(&a, &b, spec) {
+                Some(cancel) => {
+                    println!("Cancelled   : {} leading bits", cancel.bits);
+                    match cancel.amplification {
+                        Some(gain) => println!(
+                            "Rel. error  : x{} ({} of {} significant bits left)",
+                            format_rational(&gain, display),
+                            (spec.significand_bits as u32 + 1).saturating_sub(cancel.bits),
+                            spec.significand_bits + 1
+                        ),
+                        None => println!("Rel. error  : (unbounded, exact zero difference)"),
+                    }
+                }
+                None => println!("Cancelled   : (undefined for NaN/Infinity)"),
+            }
+        }
+        Command::Mul { a, b, hex } => {
+            let a = arithmetic_operand(a, *hex, spec, rounding, display)?;
+            let b = arithmetic_operand(b, *hex, spec, rounding, display)?;
+            let (product, exact) = mul_softfloats(&a, &b, spec, rounding);
+            let product = result(product);
+            println!("Format      : {}", spec.name);
+            println!("A           : {}", describe_softfloat(&a, spec, display)?);
+            println!("B           : {}", describe_softfloat(&b, spec, display)?);
+            println!(
+                "Product     : {}",
+                describe_softfloat(&product, spec, display)?
+            );
+            println!(
+                "Overflow    : {}",
+                if overflowed(&exact, &product) {
+                    "yes"
                 } else {
-                    (kept_value + BigUint::one(), false)
+                    "no"
                 }
+            );
+        }
+        Command::Rem { a, b, hex } | Command::Fmod { a, b, hex } => {
+            let quotient = match command {
+                Command::Rem { .. } => QuotientRounding::NearestEven,
+                _ => QuotientRounding::TowardZero,
+            };
+            let a = arithmetic_operand(a, *hex, spec, rounding, display)?;
+            let b = arithmetic_operand(b, *hex, spec, rounding, display)?;
+            let rem = result(remainder_softfloats(&a, &b, spec, rounding, quotient));
+            println!("Format      : {}", spec.name);
+            println!("A           : {}", describe_softfloat(&a, spec, display)?);
+            println!("B           : {}", describe_softfloat(&b, spec, display)?);
+            println!("Remainder   : {}", describe_softfloat(&rem, spec, display)?);
+        }
+        Command::NearestWith { value, property } => {
+            let value = match parse_decimal_with(value, display.decimal_separator)? {
+                ParsedValue::Finite(v) => v,
+                _ => bail!("nearest-with requires a finite decimal"),
+            };
+            println!("Format      : {}", spec.name);
+            println!("Input       : {}", format_rational(&value, display));
+            match nearest_with(&value, spec, *property)? {
+                Some(sf) => println!("Nearest     : {}", describe_softfloat(&sf, spec, display)?),
+                None => println!("Nearest     : (no representable value has this property)"),
+            }
+        }
+        Command::Midpoint { value, hex } => {
+            let sf = if *hex {
+                bits_to_softfloat(&hex_to_bits(value, total_bits(spec)?)?, spec)?
             } else {
-                (kept_value, false)
+                decimal_operand(value, spec, rounding, display)?
+            };
+            println!("Format      : {}", spec.name);
+            println!("Value       : {}", describe_softfloat(&sf, spec, display)?);
+            match rounding_midpoints(&sf, spec)? {
+                Some((lower, upper)) => {
+                    println!("Lower mid   : {}", format_rational(&lower, display));
+                    println!("Upper mid   : {}", format_rational(&upper, display));
+                }
+                None => println!("Midpoints   : (undefined for {:?})", sf.class),
             }
         }
-    }
-}
-
-fn bits_to_uint(bits: &[u8]) -> BigUint {
-    let mut value = BigUint::zero();
-    for &b in bits {
-        value <<= 1;
-        if b == 1 {
-            value += 1u8;
+        Command::Preimage { hex, digits } => {
+            let sf = bits_to_softfloat(&hex_to_bits(hex, total_bits(spec)?)?, spec)?;
+            println!("Format      : {}", spec.name);
+            println!("Pattern     : {}", describe_softfloat(&sf, spec, display)?);
+            let Some(interval) = preimage(&sf, spec, rounding)? else {
+                println!("Preimage    : (undefined for {:?})", sf.class);
+                return Ok(());
+            };
+            let end = |end: &Option<BigRational>, unbounded: &str| match end {
+                Some(end) => format_rational(end, display),
+                None => unbounded.to_string(),
+            };
+            println!(
+                "Preimage    : {}{}, {}{}",
+                if interval.lo_closed { "[" } else { "(" },
+                end(&interval.lo, "-inf"),
+                end(&interval.hi, "+inf"),
+                if interval.hi_closed { "]" } else { ")" }
+            );
+            if let Some(digits) = digits {
+                let places = DisplayOptions {
+                    precision: *digits as usize,
+                    trailing_zeros: true,
+                    ..*display
+                };
+                let scale = BigRational::from_integer(BigInt::from(10u32).pow(*digits));
+                match interval.decimals_with_digits(*digits) {
+                    Some((first, last)) => println!(
+                        "Decimals    : {} with {digits} places, {} to {}",
+                        &last - &first + 1,
+                        format_rational(&(BigRational::from_integer(first) / &scale), &places),
+                        format_rational(&(BigRational::from_integer(last) / &scale), &places)
+                    ),
+                    None if interval.lo.is_none() || interval.hi.is_none() => {
+                        println!("Decimals    : unbounded")
+                    }
+                    None => println!("Decimals    : none with {digits} places"),
+                }
+            }
         }
-    }
-    value
-}
-
-fn softfloat_to_bits(sf: &SoftFloat, spec: &FloatSpec) -> String {
-    let mut out = String::with_capacity(total_bits(spec).unwrap_or(0));
-    out.push(if sf.sign { '1' } else { '0' });
-
-    let exp_bits = spec.exponent_bits;
-    let frac_bits = spec.significand_bits;
-
-    match sf.class {
-        Class::PosInfinity | Class::NegInfinity => {
-            out.push_str(&"1".repeat(exp_bits));
-            out.push_str(&"0".repeat(frac_bits));
+        Command::Posit { hex, nbits, es } => {
+            let bits = hex_to_bits(hex, *nbits)?;
+            let decoded = decode_posit(&bits, *es)?;
+            println!("Format      : posit<{nbits},{es}>");
+            println!("Binary      : {bits}");
+            let fields = match decoded {
+                PositDecoding::Zero => {
+                    println!(
+                        "Value       : {}",
+                        format_rational(&BigRational::zero(), display)
+                    );
+                    return Ok(());
+                }
+                PositDecoding::NaR => {
+                    println!("Value       : NaR");
+                    return Ok(());
+                }
+                PositDecoding::Finite(fields) => fields,
+            };
+            println!("Sign        : {}", if fields.sign { "-" } else { "+" });
+            println!(
+                "Regime      : k = {} ({} bits)",
+                fields.regime, fields.regime_bits
+            );
+            println!(
+                "Exponent    : e = {} ({} of {es} bits stored)",
+                fields.exponent, fields.exponent_bits
+            );
+            println!(
+                "Fraction    : 0x{:X} ({} bits)",
+                fields.fraction, fields.fraction_bits
+            );
+            println!(
+                "Decomposed  : {}useed^{} * 2^{} * {} (useed = 2^{})",
+                if fields.sign { "-" } else { "" },
+                fields.regime,
+                fields.exponent,
+                format_rational(&fields.significand(), display),
+                1u64 << es
+            );
+            println!(
+                "Value       : {}",
+                format_rational(&fields.value(), display)
+            );
         }
-        Class::Nan => {
-            out.push_str(&"1".repeat(exp_bits));
-            out.push_str(&"1".repeat(frac_bits));
+        Command::Specials => {
+            println!("Format      : {}", spec.name);
+            for (name, soft) in special_values(spec) {
+                match soft {
+                    Some(soft) => {
+                        let hex = display
+                            .hex_case
+                            .apply(&bits_to_hex(&softfloat_to_bits(&soft, spec)?));
+                        let value = match softfloat_to_rational(&soft, spec) {
+                            Some(_) if soft.class == Class::Zero && soft.sign => "-0".to_string(),
+                            Some(v) => format_rational(&v, display),
+                            None => format!("{:?}", soft.class),
+                        };
+                        println!("{name:<12}: {hex}  {value}");
+                    }
+                    None => println!("{name:<12}: (not encoded by {})", spec.name),
+                }
+            }
         }
-        Class::Zero | Class::Subnormal => {
-            out.push_str(&"0".repeat(exp_bits));
-            out.push_str(&format!("{:0width$b}", sf.significand, width = frac_bits));
+        Command::Bfp { layout, values } => {
+            let values = values
+                .iter()
+                .map(
+                    |raw| match parse_decimal_with(raw, display.decimal_separator)? {
+                        ParsedValue::Finite(v) => Ok(v),
+                        _ => bail!("bfp requires finite decimals, got {raw}"),
+                    },
+                )
+                .collect::<Result<Vec<_>>>()?;
+            println!(
+                "Layout      : block={}, mant={}",
+                layout.block, layout.mantissa_bits
+            );
+            for (i, block) in encode_bfp(&values, layout, rounding).iter().enumerate() {
+                let mantissas: Vec<String> =
+                    block.mantissas.iter().map(|m| m.to_string()).collect();
+                println!(
+                    "Block {i:<6}: exponent {}, mantissas [{}]",
+                    block.shared_exponent,
+                    mantissas.join(", ")
+                );
+            }
         }
-        Class::Normal => {
-            let biased = (sf.exponent + bias(spec)) as u64;
-            out.push_str(&format!("{:0width$b}", biased, width = exp_bits));
-            out.push_str(&format!("{:0width$b}", sf.significand, width = frac_bits));
+        Command::Compare {
+            actual,
+            expected,
+            hex,
+            assume_subnormal_as_zero_in_compare,
+        } => {
+            let operand = |raw: &str| -> Result<SoftFloat> {
+                if *hex {
+                    bits_to_softfloat(&hex_to_bits(raw, total_bits(spec)?)?, spec)
+                } else {
+                    decimal_operand(raw, spec, rounding, display)
+                }
+            };
+            let actual = operand(actual)?;
+            let expected = operand(expected)?;
+            let (equal, ulps) = compare_softfloats(
+                &actual,
+                &expected,
+                spec,
+                *assume_subnormal_as_zero_in_compare,
+            )?;
+            println!("Format      : {}", spec.name);
+            println!(
+                "Actual      : {}",
+                describe_softfloat(&actual, spec, display)?
+            );
+            println!(
+                "Expected    : {}",
+                describe_softfloat(&expected, spec, display)?
+            );
+            println!("Equal       : {}", if equal { "yes" } else { "no" });
+            match ulps {
+                Some(ulps) => println!("ULP diff    : {ulps}"),
+                None => println!("ULP diff    : (undefined for NaN)"),
+            }
+        }
+        Command::Selftest { formats, samples } => {
+            let formats = if formats.is_empty() {
+                FormatChoice::value_variants()
+            } else {
+                formats.as_slice()
+            };
+            if formats
+                .iter()
+                .any(|choice| matches!(choice, FormatChoice::Custom))
+            {
+                bail!("selftest checks the built-in presets; custom has no fixed layout to check");
+            }
+            let specs: Vec<FloatSpec> = formats
+                .iter()
+                .filter_map(|&choice| preset_spec(choice))
+                .collect();
+            let width = specs.iter().map(|spec| spec.name.len()).max().unwrap_or(0);
+            let mut failed = 0;
+            for spec in &specs {
+                let report = self_test(spec, *samples, 1)?;
+                println!(
+                    "{:<width$}: {} checks, {} failed",
+                    spec.name,
+                    report.checks,
+                    report.failures.len()
+                );
+                for failure in &report.failures {
+                    println!("  {failure}");
+                }
+                failed += report.failures.len();
+            }
+            if failed > 0 {
+                bail!("selftest: {failed} checks failed");
+            }
+        }
+        Command::Completions { shell } => {
+            clap_complete::generate(*shell, &mut Cli::command(), "afcvt", &mut io::stdout());
+        }
+        Command::Manpage => {
+            clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?;
         }
     }
-
-    out
+    Ok(())
 }
 
-fn bits_to_hex(bits: &str) -> String {
-    let padded_len = ((bits.len() + 3) / 4) * 4;
-    let mut padded = bits.to_string();
-    while padded.len() < padded_len {
-        padded.insert(0, '0');
-    }
-
-    padded
-        .as_bytes()
-        .chunks(4)
-        .map(|chunk| {
-            let s = std::str::from_utf8(chunk).unwrap();
-            let v = u8::from_str_radix(s, 2).unwrap();
-            format!("{:x}", v)
-        })
-        .collect::<String>()
-        .trim_start_matches('0')
-        .to_string()
-        .to_uppercase()
+/// The label/value rows of the text report, rendered with every colon in one
+/// column: the wider of `min_width` and the longest label.
+struct TextReport {
+    min_width: usize,
+    rows: Vec<(String, String)>,
 }
 
-fn hex_to_bits(hex: &str, total_bits: usize) -> Result<String> {
-    let cleaned = hex.trim().trim_start_matches("0x").trim_start_matches("0X");
-    let bits_needed = total_bits;
-    let expected_hex = (bits_needed + 3) / 4;
-    let mut padded = cleaned.to_string();
-    if padded.len() < expected_hex {
-        padded = "0".repeat(expected_hex - padded.len()) + &padded;
-    }
-    if padded.len() != expected_hex {
-        bail!(
-            "hex length ({}) does not match expected bits {}",
-            padded.len(),
-            bits_needed
-        );
-    }
-    let mut bits = String::with_capacity(bits_needed);
-    for ch in padded.chars() {
-        let val = ch
-            .to_digit(16)
-            .ok_or_else(|| anyhow!("invalid hex digit: {ch}"))?;
-        bits.push_str(&format!("{:04b}", val));
-    }
-    if bits.len() > bits_needed {
-        bits = bits[bits.len() - bits_needed..].to_string();
+impl TextReport {
+    fn new(min_width: usize) -> Self {
+        Self {
+            min_width,
+            rows: Vec::new(),
+        }
     }
-    Ok(bits)
-}
 
-fn bits_to_softfloat(bits: &str, spec: &FloatSpec) -> Result<SoftFloat> {
-    let cleaned = bits
-        .trim()
-        .strip_prefix("0b")
-        .or_else(|| bits.trim().strip_prefix("0B"))
-        .unwrap_or_else(|| bits.trim());
-    let total = total_bits(spec)?;
-    if cleaned.len() != total {
-        bail!("expected {} bits, got {}", total, cleaned.len());
+    fn row(&mut self, label: &str, value: impl std::fmt::Display) {
+        self.rows.push((label.to_string(), value.to_string()));
     }
-    if !cleaned.chars().all(|c| c == '0' || c == '1') {
-        bail!("bits must contain only 0 or 1");
-    }
-
-    let sign = cleaned.as_bytes()[0] == b'1';
-    let exp_bits = &cleaned[1..1 + spec.exponent_bits];
-    let frac_bits = &cleaned[1 + spec.exponent_bits..];
 
-    let exp_val = usize::from_str_radix(exp_bits, 2)?;
-    let mantissa = BigUint::parse_bytes(frac_bits.as_bytes(), 2)
-        .ok_or_else(|| anyhow!("invalid mantissa bits"))?;
-
-    let all_exp_ones = exp_bits.chars().all(|c| c == '1');
-    let all_exp_zero = exp_bits.chars().all(|c| c == '0');
-    let all_frac_zero = mantissa.is_zero();
-
-    let bias = bias(spec);
-    let min_exp = min_exponent(spec);
-
-    let class;
-    let exponent;
+    /// An indented continuation line printed as-is, outside the label column.
+    fn detail(&mut self, line: String) {
+        self.rows.push((String::new(), line));
+    }
+}
 
-    if all_exp_ones {
-        class = if all_frac_zero {
-            if sign {
-                Class::NegInfinity
-            } else {
-                Class::PosInfinity
+impl std::fmt::Display for TextReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .fold(self.min_width, usize::max);
+        for (label, value) in &self.rows {
+            match (label.is_empty(), value.is_empty()) {
+                (true, _) => writeln!(f, "{value}")?,
+                (false, true) => writeln!(f, "{label:<width$}:")?,
+                (false, false) => writeln!(f, "{label:<width$}: {value}")?,
             }
-        } else {
-            Class::Nan
-        };
-        exponent = max_exponent(spec);
-    } else if all_exp_zero {
-        class = if all_frac_zero {
-            Class::Zero
-        } else {
-            Class::Subnormal
-        };
-        exponent = min_exp;
-    } else {
-        class = Class::Normal;
-        exponent = exp_val as i32 - bias;
+        }
+        Ok(())
     }
+}
 
-    Ok(SoftFloat {
-        class,
-        sign,
-        exponent,
-        significand: mantissa,
-    })
+#[derive(Debug)]
+enum Input {
+    Decimal(String),
+    Bits(String),
+    Hex(String, Endianness),
+    Fields(String, String, String),
 }
 
-fn max_exponent(spec: &FloatSpec) -> i32 {
-    bias(spec)
+/// The single positional under --interpret. `auto` only claims inputs with
+/// exactly the layout's hex width, so `1.5` or `100` stay decimals.
+fn positional_input(raw: String, cli: &Cli, spec: &FloatSpec) -> Result<Input> {
+    Ok(match cli.interpret {
+        Interpret::Hex => Input::Hex(raw, cli.endianness),
+        Interpret::Auto if looks_like_encoding(&raw, spec)? => Input::Hex(raw, cli.endianness),
+        Interpret::Decimal | Interpret::Auto => Input::Decimal(raw),
+    })
 }
 
-fn softfloat_to_rational(sf: &SoftFloat, spec: &FloatSpec) -> Option<BigRational> {
-    match sf.class {
-        Class::PosInfinity | Class::NegInfinity | Class::Nan => None,
-        Class::Zero => Some(BigRational::zero()),
-        Class::Subnormal => {
-            let denom = BigInt::one() << spec.significand_bits;
-            let sig = BigRational::new(
-                sf.significand.to_bigint().unwrap_or_else(BigInt::zero),
-                denom,
-            );
-            let value = sig * pow2(min_exponent(spec));
-            Some(if sf.sign { -value } else { value })
-        }
-        Class::Normal => {
-            let denom = BigInt::one() << spec.significand_bits;
-            let leading = BigRational::one();
-            let frac = BigRational::new(
-                sf.significand.to_bigint().unwrap_or_else(BigInt::zero),
-                denom,
-            );
-            let sig = leading + frac;
-            let value = sig * pow2(sf.exponent);
-            Some(if sf.sign { -value } else { value })
-        }
+/// Orders `(totalOrder key, row)` pairs for --sort; stable, so equal keys keep
+/// their input order.
+fn sort_rows<T>(rows: &mut [(BigInt, T)], order: Option<SortOrder>) {
+    match order {
+        Some(SortOrder::Asc) => rows.sort_by(|a, b| a.0.cmp(&b.0)),
+        Some(SortOrder::Desc) => rows.sort_by(|a, b| b.0.cmp(&a.0)),
+        None => {}
     }
 }
 
-fn format_rational(value: &BigRational, precision: usize, notation: Notation) -> String {
-    if value.is_zero() {
-        return "0".to_string();
+/// Quantizes every positional decimal, keeping each one's input position,
+/// in --sort order. Encoded inputs would silently replace the decimals, so
+/// they are rejected.
+fn batch_rows<'a>(
+    cli: &'a Cli,
+    spec: &FloatSpec,
+    display: &DisplayOptions,
+) -> Result<Vec<(usize, &'a str, SoftFloat)>> {
+    if cli.bits.is_some() || cli.hex.is_some() || cli.native_hex.is_some() || cli.sign.is_some() {
+        bail!("several decimals cannot be combined with --bits, --hex, --native-hex, or --sign");
     }
+    if cli.interpret != Interpret::Decimal {
+        bail!("--interpret applies to a single positional input");
+    }
+    let mut rows = cli
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let soft = decimal_operand(raw, spec, cli.rounding, display)?;
+            Ok((total_order_key(&soft, spec)?, (i, raw.as_str(), soft)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sort_rows(&mut rows, cli.sort);
+    Ok(rows
+        .into_iter()
+        .map(|(_, (i, raw, soft))| (i, raw, soft))
+        .collect())
+}
 
-    let sign = value.is_negative();
-    let abs = value.abs();
-    let integer = (abs.numer() / abs.denom())
-        .to_bigint()
-        .unwrap_or_else(BigInt::zero);
-    let mut remainder = abs - BigRational::from_integer(integer.clone());
+/// The `--summary` footer: the tally of every row, re-parsing each input
+/// for its exact value.
+fn batch_summary(
+    rows: &[(usize, &str, SoftFloat)],
+    spec: &FloatSpec,
+    display: &DisplayOptions,
+) -> Result<TextReport> {
+    let mut summary = BatchSummary::default();
+    for (_, raw, soft) in rows {
+        let source = parse_decimal_with(raw, display.decimal_separator)?;
+        summary.record(&source, soft, spec);
+    }
+    let mut text = TextReport::new(12);
+    text.row("Values", summary.total);
+    text.row("Inexact", summary.inexact);
+    text.row("Overflow", summary.overflow);
+    text.row("Underflow", summary.underflow);
+    text.row("Subnormal", summary.subnormal);
+    match &summary.max_ulp_error {
+        Some(max) => text.row(
+            "Max error",
+            format!("{} ULP", format_rational(max, display)),
+        ),
+        None => text.row("Max error", "(no finite results)"),
+    }
+    Ok(text)
+}
 
-    let mut digits = String::new();
-    for _ in 0..precision {
-        remainder *= BigInt::from(10);
-        let digit = (remainder.numer() / remainder.denom())
-            .to_bigint()
-            .unwrap_or_else(BigInt::zero);
-        digits.push_str(&format!("{}", digit));
-        remainder -= BigRational::from_integer(digit);
-        if remainder.is_zero() {
-            break;
+/// Groups batch rows by bit pattern, in order of each pattern's first row,
+/// listing the inputs that collided on it.
+fn dedup_rows<'a>(
+    rows: Vec<(usize, &'a str, SoftFloat)>,
+    spec: &FloatSpec,
+) -> Result<Vec<(SoftFloat, Vec<&'a str>)>> {
+    let mut groups: Vec<(String, SoftFloat, Vec<&'a str>)> = Vec::new();
+    for (_, raw, soft) in rows {
+        let bits = softfloat_to_bits(&soft, spec)?;
+        match groups.iter_mut().find(|(seen, _, _)| *seen == bits) {
+            Some((_, _, inputs)) => inputs.push(raw),
+            None => groups.push((bits, soft, vec![raw])),
         }
     }
+    Ok(groups
+        .into_iter()
+        .map(|(_, soft, inputs)| (soft, inputs))
+        .collect())
+}
 
-    let mut repr = if digits.is_empty() {
-        format!("{}", integer)
-    } else {
-        format!("{}.{digits}", integer)
+/// How numbers are printed. `--digits-after-point N` stands in for
+/// `--precision N --trailing-zeros`, so every plain expansion shows exactly N
+/// fractional digits; scientific mantissas would count differently, so the
+/// two are not combined.
+fn display_options(cli: &Cli) -> Result<DisplayOptions> {
+    let (precision, trailing_zeros) = match cli.digits_after_point {
+        Some(_) if matches!(cli.notation, Notation::Scientific) => {
+            bail!("--digits-after-point applies only to --notation plain")
+        }
+        Some(digits) => (digits, true),
+        None => (cli.precision, cli.trailing_zeros),
     };
+    let display = DisplayOptions {
+        precision,
+        notation: cli.notation,
+        trailing_zeros,
+        decimal_separator: cli.decimal_separator,
+        hex_case: cli.hex_case,
+        plus_sign: cli.plus_sign,
+    };
+    validate_decimal_separator(display.decimal_separator)?;
+    Ok(display)
+}
 
-    if let Notation::Scientific = notation {
-        repr = to_scientific(&repr);
+fn resolve_format(cli: &Cli) -> Result<FloatSpec> {
+    let spec = match preset_spec(cli.format) {
+        Some(spec) => spec,
+        None => {
+            let e = cli
+                .exponent_bits
+                .ok_or_else(|| anyhow!("--exp is required for --format=custom"))?;
+            let s = cli
+                .significand_bits
+                .ok_or_else(|| anyhow!("--mant is required for --format=custom"))?;
+            custom_spec(e, s)?
+        }
+    };
+    let spec = match cli.bias {
+        Some(bias) => with_bias(&spec, bias)?,
+        None => spec,
+    };
+    if cli.no_special_exponent {
+        return with_specials(&spec, false, NanPolicy::None);
     }
-
-    if sign { format!("-{repr}") } else { repr }
+    if cli.no_infinity || cli.nan_encoding.is_some() {
+        let has_infinity = spec.has_infinity && !cli.no_infinity;
+        return with_specials(
+            &spec,
+            has_infinity,
+            cli.nan_encoding.unwrap_or(spec.nan_encoding),
+        );
+    }
+    Ok(spec)
 }
 
-fn to_scientific(num: &str) -> String {
-    if num == "0" {
-        return "0".to_string();
-    }
-    let mut cleaned = num.replace('.', "");
-    let mut exponent = 0i32;
-    let negative = cleaned.starts_with('-');
-    if negative {
-        cleaned.remove(0);
-    }
-    let mut chars: Vec<char> = cleaned.chars().collect();
-    while !chars.is_empty() && chars[0] == '0' {
-        chars.remove(0);
-        exponent -= 1;
-    }
-    let first = chars.get(0).cloned().unwrap_or('0');
-    let rest: String = chars.iter().skip(1).collect();
-    let mantissa = if rest.is_empty() {
-        format!("{first}")
-    } else {
-        format!("{first}.{}", rest.trim_end_matches('0'))
+/// Target of `--to`, resolved like `--format` but from the `--to-*` widths.
+fn resolve_target_format(cli: &Cli) -> Result<Option<FloatSpec>> {
+    let Some(choice) = cli.to else {
+        return Ok(None);
     };
-    let exp_str = format!(
-        "e{:+}",
-        exponent + ((num.find('.').unwrap_or(num.len()) as i32) - 1)
-    );
-    let sign_prefix = if negative { "-" } else { "" };
-    format!("{sign_prefix}{mantissa}{exp_str}")
+    let spec = match preset_spec(choice) {
+        Some(spec) => spec,
+        None => {
+            let e = cli
+                .to_exponent_bits
+                .ok_or_else(|| anyhow!("--to-exp is required for --to=custom"))?;
+            let s = cli
+                .to_significand_bits
+                .ok_or_else(|| anyhow!("--to-mant is required for --to=custom"))?;
+            custom_spec(e, s)?
+        }
+    };
+    Ok(Some(spec))
 }
+
 #[cfg(test)]
-mod tests;
+mod cli_tests;
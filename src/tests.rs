@@ -15,12 +15,16 @@ fn fp32_roundtrip_for_one_point_five() {
 		name: "FP32",
 		exponent_bits: 8,
 		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
 	};
 	let parsed = ParsedValue::Finite(BigRational::new(BigInt::from(3), BigInt::from(2)));
 	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
 	assert_eq!(soft.class, Class::Normal);
 	assert_eq!(soft.exponent, 0);
-	let bits = softfloat_to_bits(&soft, &spec);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
 	assert_eq!(bits, "00111111110000000000000000000000");
 }
 
@@ -30,6 +34,10 @@ fn bits_input_allows_0b_prefix() {
 		name: "FP32",
 		exponent_bits: 8,
 		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
 	};
 	let parsed =
 		bits_to_softfloat("0b00111111110000000000000000000000", &spec).expect("parse bits");
@@ -43,77 +51,1939 @@ fn hex_input_allows_0x_prefix() {
 		name: "FP32",
 		exponent_bits: 8,
 		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
 	};
 	let bits = hex_to_bits("0X3FC00000", total_bits(&spec).unwrap()).expect("hex to bits");
 	assert_eq!(bits, "00111111110000000000000000000000");
 }
 
+#[test]
+fn hex_input_rejects_non_hex_characters() {
+	let err = hex_to_bits("0xZZ", 32).expect_err("non-hex input");
+	assert_eq!(err.to_string(), "hex input contains non-hex character 'Z'");
+}
+
+#[test]
+fn hex_input_rejects_too_many_nibbles() {
+	let err = hex_to_bits("0x3FC000000", 32).expect_err("over-long input");
+	assert_eq!(
+		err.to_string(),
+		"too many nibbles for a 32-bit format (got 9, max 8)"
+	);
+}
+
+#[test]
+fn hex_input_rejects_bits_beyond_odd_width() {
+	assert_eq!(hex_to_bits("0x1FF", 9).unwrap(), "111111111");
+	let err = hex_to_bits("0x2FF", 9).expect_err("value wider than format");
+	assert_eq!(err.to_string(), "hex value 0x2FF does not fit in a 9-bit format");
+}
+
 #[test]
 fn decimal_zero_point_one_matches_reference_bits() {
 	let spec = FloatSpec {
 		name: "FP32",
 		exponent_bits: 8,
-		significand_bits: 23,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let parsed = parse_decimal("0.1").expect("parse decimal");
+	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
+	assert_eq!(bits, "00111101110011001100110011001101");
+}
+
+#[test]
+fn decimal_negative_two_point_five_matches_reference_bits() {
+	let spec = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let parsed = parse_decimal("-2.5").expect("parse decimal");
+	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
+	assert_eq!(bits, "11000000001000000000000000000000");
+}
+
+fn parsed_rational(raw: &str) -> BigRational {
+	match parse_decimal(raw).expect("parse decimal") {
+		ParsedValue::Finite(v) => v,
+		other => panic!("expected finite value for {raw}, got {other:?}"),
+	}
+}
+
+#[test]
+fn scientific_inputs_parse_to_exact_rationals() {
+	assert_eq!(
+		parsed_rational("1E+10"),
+		BigRational::from_integer(BigInt::from(10_000_000_000u64))
+	);
+	assert_eq!(
+		parsed_rational("1.5e-3"),
+		BigRational::new(BigInt::from(3), BigInt::from(2000))
+	);
+	assert_eq!(
+		parsed_rational("2.e4"),
+		BigRational::from_integer(BigInt::from(20000))
+	);
+	assert_eq!(
+		parsed_rational("+1.5E+2"),
+		BigRational::from_integer(BigInt::from(150))
+	);
+}
+
+#[test]
+fn fp16_one_point_five_matches_reference_bits() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	let parsed = parse_decimal("1.5").expect("parse decimal");
+	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
+	assert_eq!(bits, "0011111000000000");
+}
+
+#[test]
+fn bfloat16_pi_matches_reference_bits() {
+	let spec = FloatSpec {
+		name: "bfloat16",
+		exponent_bits: 8,
+		significand_bits: 7,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let parsed = parse_decimal("3.14159265").expect("parse decimal");
+	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
+	assert_eq!(bits, "0100000001001001");
+}
+
+#[test]
+fn fp64_negative_value_matches_reference_bits() {
+	let spec = FloatSpec {
+		name: "FP64",
+		exponent_bits: 11,
+		significand_bits: 52,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 1023,
+		explicit_integer_bit: false,
+	};
+	let parsed = parse_decimal("-123.456").expect("parse decimal");
+	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).expect("encode bits");
+	assert_eq!(
+		bits,
+		"1100000001011110110111010010111100011010100111111011111001110111"
+	);
+}
+
+#[test]
+fn native_parse_agrees_with_exact_half_even() {
+	let fp32 = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let fp64 = FloatSpec {
+		name: "FP64",
+		exponent_bits: 11,
+		significand_bits: 52,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 1023,
+		explicit_integer_bit: false,
+	};
+	for raw in ["0.1", "-2.5", "3.14159265", "1e-40", "65504"] {
+		for (native, spec) in [(NativeFloat::F32, &fp32), (NativeFloat::F64, &fp64)] {
+			let parsed = parse_decimal(raw).expect("parse decimal");
+			let exact = softfloat_to_bits(&parsed_to_softfloat(&parsed, spec, RoundingMode::HalfEven), spec)
+				.expect("encode bits");
+			let native = native_bits(raw, native, spec).expect("native bits");
+			assert_eq!(native, exact, "native mismatch for {raw} in {}", spec.name);
+		}
+	}
+}
+
+#[test]
+fn native_parse_rejects_mismatched_format() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	assert!(native_bits("1.5", NativeFloat::F32, &spec).is_err());
+}
+
+fn display(precision: usize, notation: Notation) -> DisplayOptions {
+	DisplayOptions {
+		precision,
+		notation,
+		trailing_zeros: false,
+		decimal_separator: '.',
+		hex_case: HexCase::Upper,
+		plus_sign: false,
+	}
+}
+
+#[test]
+fn trailing_zeros_pad_terminating_expansion() {
+	let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+	assert_eq!(format_rational(&half, &display(10, Notation::Plain)), "0.5");
+	let padded = DisplayOptions {
+		trailing_zeros: true,
+		..display(10, Notation::Plain)
+	};
+	assert_eq!(format_rational(&half, &padded), "0.5000000000");
+}
+
+#[test]
+fn gap_near_one_in_fp16_is_two_pow_minus_ten() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	let one = BigRational::one();
+	assert_eq!(ulp_gap(&one, &spec), pow2(-10));
+	let thousand = BigRational::from_integer(BigInt::from(1000));
+	assert_eq!(ulp_gap(&thousand, &spec), pow2(-1));
+}
+
+#[test]
+fn gap_is_constant_across_fp16_subnormals() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	let tiny = pow2(-24);
+	let largest_subnormal = pow2(-14) - pow2(-24);
+	assert_eq!(ulp_gap(&tiny, &spec), pow2(-24));
+	assert_eq!(ulp_gap(&largest_subnormal, &spec), pow2(-24));
+	assert_eq!(ulp_gap(&BigRational::zero(), &spec), pow2(-24));
+}
+
+#[test]
+fn two_sum_error_term_reconstructs_exact_fp32_sum() {
+	let spec = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let a = parsed_to_softfloat(&parse_decimal("1").unwrap(), &spec, RoundingMode::HalfEven);
+	let b = parsed_to_softfloat(
+		&ParsedValue::Finite(pow2(-30) * BigInt::from(3)),
+		&spec,
+		RoundingMode::HalfEven,
+	);
+	let (sum, err) = two_sum_softfloats(&a, &b, &spec, RoundingMode::HalfEven);
+	let (e, exact) = err.expect("finite sum has an error term");
+	assert!(exact);
+	let s = softfloat_to_rational(&sum, &spec).unwrap();
+	let e = softfloat_to_rational(&e, &spec).unwrap();
+	assert_eq!(s, BigRational::one());
+	assert_eq!(
+		s + e,
+		softfloat_to_rational(&a, &spec).unwrap() + softfloat_to_rational(&b, &spec).unwrap()
+	);
+}
+
+#[test]
+fn zero_bits_encode_as_single_hex_digit() {
+	assert_eq!(bits_to_hex("0000000000000000"), "0");
+}
+
+#[test]
+fn add_of_opposite_infinities_is_nan() {
+	let sum = add_values(&ParsedValue::PosInfinity, &ParsedValue::NegInfinity);
+	assert!(matches!(sum, ParsedValue::Nan));
+}
+
+#[test]
+fn significand_scale_reweights_fraction_field() {
+	let spec = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let soft = bits_to_softfloat("00111111110000000000000000000000", &spec).expect("parse bits");
+	let default = softfloat_to_rational(&soft, &spec).unwrap();
+	let unit = softfloat_to_rational_scaled(&soft, &spec, &BigRational::one()).unwrap();
+	let doubled =
+		softfloat_to_rational_scaled(&soft, &spec, &BigRational::from_integer(BigInt::from(2)))
+			.unwrap();
+	assert_eq!(default, unit);
+	assert_eq!(default, BigRational::new(BigInt::from(3), BigInt::from(2)));
+	assert_eq!(doubled, BigRational::from_integer(BigInt::from(2)));
+}
+
+#[test]
+fn over_wide_significand_is_rejected_when_encoding() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	let soft = SoftFloat {
+		class: Class::Normal,
+		sign: false,
+		exponent: 0,
+		significand: BigUint::one() << 10,
+	};
+	let err = softfloat_to_bits(&soft, &spec).expect_err("over-wide significand");
+	assert!(err.to_string().contains("does not fit in 10 fraction bits"));
+}
+
+#[test]
+fn little_endian_hex_of_one_decodes_in_fp64() {
+	let spec = FloatSpec {
+		name: "FP64",
+		exponent_bits: 11,
+		significand_bits: 52,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 1023,
+		explicit_integer_bit: false,
+	};
+	let total = total_bits(&spec).unwrap();
+	let swapped = swap_hex_bytes("000000000000F03F", total).expect("swap bytes");
+	assert_eq!(swapped, "3FF0000000000000");
+	let soft = bits_to_softfloat(&hex_to_bits(&swapped, total).unwrap(), &spec).unwrap();
+	assert_eq!(softfloat_to_rational(&soft, &spec), Some(BigRational::one()));
+}
+
+#[test]
+fn fp16_product_overflow_yields_signed_infinity() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	let a = softfloat_to_parsed(
+		&parsed_to_softfloat(&parse_decimal("300").unwrap(), &spec, RoundingMode::HalfEven),
+		&spec,
+	);
+	let b = softfloat_to_parsed(
+		&parsed_to_softfloat(&parse_decimal("-300").unwrap(), &spec, RoundingMode::HalfEven),
+		&spec,
+	);
+	let exact = mul_values(&a, &a);
+	let product = parsed_to_softfloat(&exact, &spec, RoundingMode::HalfEven);
+	assert_eq!(product.class, Class::PosInfinity);
+	assert!(overflowed(&exact, &product));
+
+	let exact = mul_values(&a, &b);
+	let product = parsed_to_softfloat(&exact, &spec, RoundingMode::HalfEven);
+	assert_eq!(product.class, Class::NegInfinity);
+	assert!(overflowed(&exact, &product));
+
+	let exact = mul_values(&b, &ParsedValue::Finite(BigRational::one()));
+	let product = parsed_to_softfloat(&exact, &spec, RoundingMode::HalfEven);
+	assert!(!overflowed(&exact, &product));
+}
+
+#[test]
+fn zero_times_infinity_is_nan() {
+	let zero = ParsedValue::Finite(BigRational::zero());
+	assert!(matches!(mul_values(&zero, &ParsedValue::NegInfinity), ParsedValue::Nan));
+}
+
+#[test]
+fn ieee_remainder_and_fmod_of_five_by_three() {
+	let five = ParsedValue::Finite(BigRational::from_integer(BigInt::from(5)));
+	let three = ParsedValue::Finite(BigRational::from_integer(BigInt::from(3)));
+	let rem = remainder_values(&five, &three, QuotientRounding::NearestEven);
+	let fmod = remainder_values(&five, &three, QuotientRounding::TowardZero);
+	assert!(matches!(rem, ParsedValue::Finite(v) if v == BigRational::from_integer(BigInt::from(-1))));
+	assert!(matches!(fmod, ParsedValue::Finite(v) if v == BigRational::from_integer(BigInt::from(2))));
+}
+
+#[test]
+fn remainder_special_operands() {
+	let one = ParsedValue::Finite(BigRational::one());
+	let zero = ParsedValue::Finite(BigRational::zero());
+	for quotient in [QuotientRounding::NearestEven, QuotientRounding::TowardZero] {
+		assert!(matches!(remainder_values(&one, &zero, quotient), ParsedValue::Nan));
+		assert!(matches!(
+			remainder_values(&ParsedValue::PosInfinity, &one, quotient),
+			ParsedValue::Nan
+		));
+		assert!(matches!(
+			remainder_values(&one, &ParsedValue::NegInfinity, quotient),
+			ParsedValue::Finite(v) if v.is_one()
+		));
+	}
+}
+
+#[test]
+fn nan_payload_is_preserved_unless_canonicalized() {
+	let spec = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let bits = hex_to_bits("7F800001", total_bits(&spec).unwrap()).unwrap();
+	let snan = bits_to_softfloat(&bits, &spec).unwrap();
+	assert_eq!(snan.class, Class::Nan);
+	assert_eq!(softfloat_to_bits(&snan, &spec).unwrap(), bits);
+	let canonical = softfloat_to_bits(&canonical_nan(&spec), &spec).unwrap();
+	assert_eq!(bits_to_hex(&canonical), "7FC00000");
+}
+
+#[test]
+fn fp16_range_fraction_of_half_max() {
+	let spec = FloatSpec {
+		name: "FP16",
+		exponent_bits: 5,
+		significand_bits: 10,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 15,
+		explicit_integer_bit: false,
+	};
+	assert_eq!(max_finite(&spec), BigRational::from_integer(BigInt::from(65504)));
+	let half = BigRational::from_integer(BigInt::from(32752));
+	let percent = range_fraction(&half, &spec) * BigInt::from(100);
+	assert_eq!(percent, BigRational::from_integer(BigInt::from(50)));
+	let log = log_range_fraction(&max_finite(&spec), &spec);
+	assert!((log - 1.0).abs() < 1e-12);
+	assert_eq!(log_range_fraction(&pow2(-24), &spec), 0.0);
+}
+
+#[test]
+fn fp32_integer_exactness_around_two_pow_24() {
+	let spec = FloatSpec {
+		name: "FP32",
+		exponent_bits: 8,
+		significand_bits: 23,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
+	};
+	let exact = BigInt::one() << 24;
+	assert!(integer_is_exact(&exact, &spec));
+	assert!(!integer_is_exact(&(&exact + 1), &spec));
+	assert!(integer_is_exact(&(&exact + 2), &spec));
+	assert!(!integer_is_exact(&(BigInt::one() << 128), &spec));
+}
+
+#[test]
+fn comma_decimal_separator_round_trips() {
+	let comma = parse_decimal_with("3,14", ',').expect("parse localized");
+	let dot = parse_decimal("3.14").expect("parse decimal");
+	assert!(matches!((comma, dot), (ParsedValue::Finite(a), ParsedValue::Finite(b)) if a == b));
+	assert!(parse_decimal_with("3.14", ',').is_err());
+	let options = DisplayOptions {
+		decimal_separator: ',',
+		..display(4, Notation::Plain)
+	};
+	let value = BigRational::new(BigInt::from(5), BigInt::from(4));
+	assert_eq!(format_rational(&value, &options), "1,25");
+}
+
+#[test]
+fn decimal_separator_rejects_ambiguous_characters() {
+	for separator in ['e', '5', '-', '/', '_'] {
+		assert!(validate_decimal_separator(separator).is_err());
+	}
+	assert!(validate_decimal_separator(',').is_ok());
+}
+
+#[test]
+fn fp32_to_bfloat16_pi_loses_mantissa_bits() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let bf16 = preset_spec(FormatChoice::Bfloat16).unwrap();
+	let pi = parsed_to_softfloat(&parse_decimal("3.14159265").unwrap(), &fp32, RoundingMode::HalfEven);
+	let (converted, diff) = diff_formats(&pi, &fp32, &bf16, RoundingMode::HalfEven);
+	assert_eq!(diff.exponent_bits_delta, 0);
+	assert_eq!(diff.significand_bits_delta, -16);
+	assert!(diff.inexact);
+	assert_eq!(softfloat_to_bits(&converted, &bf16).unwrap(), "0100000001001001");
+
+	let half = parsed_to_softfloat(&parse_decimal("0.5").unwrap(), &fp32, RoundingMode::HalfEven);
+	let (_, diff) = diff_formats(&half, &fp32, &bf16, RoundingMode::HalfEven);
+	assert!(!diff.inexact);
+}
+
+#[test]
+fn compare_flushes_subnormals_only_when_asked() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let zero = bits_to_softfloat(&hex_to_bits("00000000", 32).unwrap(), &spec).unwrap();
+	let subnormal = bits_to_softfloat(&hex_to_bits("00000003", 32).unwrap(), &spec).unwrap();
+	let (equal, ulps) = compare_softfloats(&zero, &subnormal, &spec, false).unwrap();
+	assert!(!equal);
+	assert_eq!(ulps, Some(BigUint::from(3u32)));
+	let (equal, ulps) = compare_softfloats(&zero, &subnormal, &spec, true).unwrap();
+	assert!(equal);
+	assert_eq!(ulps, Some(BigUint::zero()));
+}
+
+#[test]
+fn ordinals_straddle_signed_zero() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let neg_zero = bits_to_softfloat(&hex_to_bits("8000", 16).unwrap(), &spec).unwrap();
+	let neg_tiny = bits_to_softfloat(&hex_to_bits("8001", 16).unwrap(), &spec).unwrap();
+	let pos_tiny = bits_to_softfloat(&hex_to_bits("0001", 16).unwrap(), &spec).unwrap();
+	assert_eq!(softfloat_ordinal(&neg_zero, &spec).unwrap(), Some(BigInt::zero()));
+	let (_, ulps) = compare_softfloats(&neg_tiny, &pos_tiny, &spec, false).unwrap();
+	assert_eq!(ulps, Some(BigUint::from(2u32)));
+}
+
+#[test]
+fn binary_scientific_makes_implicit_bit_explicit() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let twelve = parsed_to_softfloat(&parse_decimal("12").unwrap(), &fp32, RoundingMode::HalfEven);
+	assert_eq!(binary_scientific(&twelve, &fp32).as_deref(), Some("1.1₂ × 2^3"));
+	let one = parsed_to_softfloat(&parse_decimal("-1").unwrap(), &fp32, RoundingMode::HalfEven);
+	assert_eq!(binary_scientific(&one, &fp32).as_deref(), Some("-1.0₂ × 2^0"));
+
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let subnormal = bits_to_softfloat(&hex_to_bits("0200", 16).unwrap(), &fp16).unwrap();
+	assert_eq!(binary_scientific(&subnormal, &fp16).as_deref(), Some("0.1₂ × 2^-14"));
+	let zero = bits_to_softfloat(&hex_to_bits("0000", 16).unwrap(), &fp16).unwrap();
+	assert_eq!(binary_scientific(&zero, &fp16).as_deref(), Some("0₂"));
+	let inf = bits_to_softfloat(&hex_to_bits("7C00", 16).unwrap(), &fp16).unwrap();
+	assert_eq!(binary_scientific(&inf, &fp16), None);
+}
+
+#[test]
+fn fp8_e4m3_uses_top_binade_and_has_single_nan() {
+	let spec = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	assert_eq!(max_finite(&spec), BigRational::from_integer(BigInt::from(448)));
+	let max = parsed_to_softfloat(&parse_decimal("448").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(softfloat_to_bits(&max, &spec).unwrap(), "01111110");
+	let nan = bits_to_softfloat("01111111", &spec).unwrap();
+	assert_eq!(nan.class, Class::Nan);
+	let top = bits_to_softfloat("01111000", &spec).unwrap();
+	assert_eq!(softfloat_to_rational(&top, &spec), Some(BigRational::from_integer(BigInt::from(256))));
+	let overflow = parsed_to_softfloat(&parse_decimal("480").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(overflow.class, Class::Nan);
+	let inf = parsed_to_softfloat(&ParsedValue::NegInfinity, &spec, RoundingMode::HalfEven);
+	assert_eq!(softfloat_to_bits(&inf, &spec).unwrap(), "11111111");
+}
+
+#[test]
+fn strict_ieee_rejects_formats_without_infinity() {
+	let e4m3 = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	let err = check_strict_ieee(&e4m3).expect_err("E4M3 has no infinities");
+	assert!(err.to_string().contains("no infinities"));
+	assert!(check_strict_ieee(&preset_spec(FormatChoice::Fp8E5m2).unwrap()).is_ok());
+	assert!(check_strict_ieee(&preset_spec(FormatChoice::Fp32).unwrap()).is_ok());
+}
+
+#[test]
+fn strict_ieee_rejects_explicit_integer_bit() {
+	let err = check_strict_ieee(&preset_spec(FormatChoice::Fp80).unwrap()).expect_err("FP80 stores its integer bit");
+	assert!(err.to_string().contains("explicit integer bit"));
+}
+
+#[test]
+fn toward_zero_error_beyond_half_ulp_in_ulp_form() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let tenth_ulps = BigRational::new(BigInt::from(9), BigInt::from(10));
+	let source = BigRational::one() + &tenth_ulps * pow2(-10);
+	let soft = parsed_to_softfloat(&ParsedValue::Finite(source.clone()), &spec, RoundingMode::TowardZero);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(stored, BigRational::one());
+	let err = &stored - &source;
+	let (whole, remainder, total) = error_in_ulps(&err, &stored, &spec);
+	assert_eq!(whole, BigInt::zero());
+	assert_eq!(remainder, err);
+	assert_eq!(total, -tenth_ulps);
+
+	let (whole, remainder, _) =
+		error_in_ulps(&(pow2(-10) * BigInt::from(3) / BigInt::from(2)), &stored, &spec);
+	assert_eq!(whole, BigInt::one());
+	assert_eq!(remainder, pow2(-11));
+}
+
+#[test]
+fn signaling_nan_serializes_as_tagged_special() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let snan = bits_to_softfloat(&hex_to_bits("7F800001", 32).unwrap(), &spec).unwrap();
+	let json = softfloat_to_value(&snan, &spec).to_json(&display(8, Notation::Plain));
+	assert_eq!(json, serde_json::json!({ "special": "snan", "payload": "0x1" }));
+
+	let qnan = bits_to_softfloat(&hex_to_bits("7FC00000", 32).unwrap(), &spec).unwrap();
+	let json = softfloat_to_value(&qnan, &spec).to_json(&display(8, Notation::Plain));
+	assert_eq!(json["special"], "qnan");
+	let inf = bits_to_softfloat(&hex_to_bits("FF800000", 32).unwrap(), &spec).unwrap();
+	let json = softfloat_to_value(&inf, &spec).to_json(&display(8, Notation::Plain));
+	assert_eq!(json, serde_json::json!({ "special": "-inf" }));
+}
+
+#[test]
+fn fp32_mantissa_prints_as_decimal() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let report = convert("1.5", &spec, RoundingMode::HalfEven, 8, Notation::Plain).unwrap();
+	assert_eq!(report.mantissa, "4194304");
+	// The largest fraction field shows every digit, with no hex or grouping.
+	let report = convert("1.9999999", &spec, RoundingMode::HalfEven, 8, Notation::Plain).unwrap();
+	assert_eq!(report.mantissa, "8388607");
+}
+
+#[test]
+fn decimal_input_tolerates_plus_sign_and_surrounding_whitespace() {
+	let expected = BigRational::new(BigInt::from(3), BigInt::from(2));
+	assert_eq!(parsed_rational("+1.5"), expected);
+	assert_eq!(parsed_rational(" 1.5 "), expected);
+	assert!(matches!(parse_decimal(" +inf").unwrap(), ParsedValue::PosInfinity));
+
+	let err = parse_decimal("- 1.5").unwrap_err();
+	assert!(err.to_string().contains("space after the sign"), "{err}");
+}
+
+#[test]
+fn exact_digit_count_matches_power_of_two_denominator() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("0.1").unwrap(), &spec, RoundingMode::HalfEven);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(format_rational(&stored, &display(40, Notation::Plain)), "0.100000001490116119384765625");
+	assert_eq!(exact_decimal_digits(&stored), Some(27));
+	assert_eq!(exact_decimal_digits(&parsed_rational("3")), Some(0));
+	assert_eq!(exact_decimal_digits(&BigRational::zero()), None);
+}
+
+#[test]
+fn round_to_bits_matches_tf32_in_an_fp32_container() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let tf32 = preset_spec(FormatChoice::Tf32).unwrap();
+	let parsed = parse_decimal("3.14159265").unwrap();
+
+	let narrowed = round_to_bits(&parsed, &fp32, 10, RoundingMode::HalfEven).unwrap();
+	let reference = parsed_to_softfloat(&parsed, &tf32, RoundingMode::HalfEven);
+	assert_eq!(
+		softfloat_to_rational(&narrowed, &fp32),
+		softfloat_to_rational(&reference, &tf32)
+	);
+	let bits = softfloat_to_bits(&narrowed, &fp32).unwrap();
+	assert_eq!(bits.len(), 32);
+	assert!(bits.ends_with(&"0".repeat(13)));
+
+	assert!(round_to_bits(&parsed, &fp32, 24, RoundingMode::HalfEven).is_err());
+}
+
+#[test]
+fn rounding_table_separates_modes_at_a_halfway_fp16_value() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	// 1 + 3·2^-11 sits halfway between 0x3C01 (odd) and 0x3C02 (even).
+	let table = all_roundings(&parse_decimal("1.00146484375").unwrap(), &spec);
+	let hex: Vec<(RoundingMode, String)> = table
+		.iter()
+		.map(|(mode, sf)| (*mode, bits_to_hex(&softfloat_to_bits(sf, &spec).unwrap())))
+		.collect();
+	assert!(matches!(hex[0], (RoundingMode::HalfEven, ref h) if h == "3C02"));
+	assert!(matches!(hex[1], (RoundingMode::TowardZero, ref h) if h == "3C01"));
+}
+
+#[test]
+fn halfway_detection_flags_only_exact_ties() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	assert!(is_exactly_halfway(&parsed_rational("1.00146484375"), &spec));
+	// Halfway between the two smallest subnormals.
+	assert!(is_exactly_halfway(&parsed_rational("8.94069671630859375e-8"), &spec));
+	assert!(!is_exactly_halfway(&parsed_rational("0.1"), &spec));
+	assert!(!is_exactly_halfway(&parsed_rational("1.5"), &spec));
+}
+
+#[test]
+fn convert_reports_fp32_one_point_five() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let report = convert("1.5", &spec, RoundingMode::HalfEven, 10, Notation::Plain).unwrap();
+	assert_eq!(report.format, "FP32");
+	assert_eq!(report.class, "Normal");
+	assert_eq!(report.exponent.unbiased, 0);
+	assert_eq!(report.exponent.field, 127);
+	assert_eq!(report.hex, "3FC00000");
+	assert_eq!(report.mantissa, "4194304");
+	assert_eq!(report.stored, serde_json::json!("1.5"));
+	assert_eq!(report.error.as_deref(), Some("0"));
+	assert_eq!(report.exactly_halfway, Some(false));
+	let json = serde_json::to_value(&report).unwrap();
+	assert_eq!(json["hex"], "3FC00000");
+}
+
+#[test]
+fn bf16_shortcut_matches_except_on_double_rounding_ties() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let bf16 = preset_spec(FormatChoice::Bfloat16).unwrap();
+	let bits = |sf: &SoftFloat| softfloat_to_bits(sf, &bf16).unwrap();
+	for raw in ["1.5", "0.1", "3.14159265", "-65504", "1e-40", "3.4e38", "1.00390625"] {
+		let (direct, via) = double_rounding(&parse_decimal(raw).unwrap(), &fp32, &bf16, RoundingMode::HalfEven);
+		assert_eq!(bits(&direct), bits(&via), "{raw}");
+	}
+	// 1 + 2^-8 + 2^-30 is just above a bf16 tie, but FP32 rounds it onto the
+	// tie, which then breaks to even: the shortcut gives 1.0, direct gives 1 + 2^-7.
+	let (direct, via) = double_rounding(
+		&parse_decimal("1.003906250931322574615478515625").unwrap(),
+		&fp32,
+		&bf16,
+		RoundingMode::HalfEven,
+	);
+	assert_eq!(bits_to_hex(&bits(&direct)), "3F81");
+	assert_eq!(bits_to_hex(&bits(&via)), "3F80");
+}
+
+#[test]
+fn hex_bytes_groups_fixed_width_hex_per_byte() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(bits_to_hex_bytes(&softfloat_to_bits(&soft, &spec).unwrap()), "3F C0 00 00");
+	assert_eq!(bits_to_hex_bytes(&"0".repeat(32)), "00 00 00 00");
+	// A 12-bit field has three nibbles; the partial byte comes first.
+	assert_eq!(bits_to_hex_bytes("101111001101"), "B CD");
+}
+
+#[test]
+fn nearest_with_finds_power_of_two_integer_and_even_mantissa() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let nearest = |raw: &str, property| {
+		let sf = nearest_with(&parsed_rational(raw), &spec, property).unwrap().unwrap();
+		softfloat_to_rational(&sf, &spec).unwrap()
+	};
+	// 2 and 4 are equally far from 3; ties go to the larger magnitude.
+	assert_eq!(nearest("3.0", Property::PowerOfTwo), parsed_rational("4"));
+	assert_eq!(nearest("-5", Property::PowerOfTwo), parsed_rational("-4"));
+	assert_eq!(nearest("2.4", Property::Integer), parsed_rational("2"));
+	// 1 + 2^-10 has an odd fraction; its neighbours are both even.
+	assert_eq!(nearest("1.0009765625", Property::EvenMantissa), parsed_rational("1.001953125"));
+	assert_eq!(nearest("70000", Property::Integer), parsed_rational("65504"));
+	// 70000 rounds to +Inf and both bracketing powers of two overflow.
+	assert_eq!(nearest("70000", Property::PowerOfTwo), parsed_rational("32768"));
+	assert_eq!(nearest("-1e9", Property::PowerOfTwo), parsed_rational("-32768"));
+}
+
+#[test]
+fn special_value_spellings_parse_consistently() {
+	for raw in ["inf", "+Inf", "infinity", "+infinity", "INFINITY", "∞", "+∞"] {
+		assert!(matches!(parse_decimal(raw).unwrap(), ParsedValue::PosInfinity), "{raw}");
+	}
+	for raw in ["-inf", "-Infinity", "-∞"] {
+		assert!(matches!(parse_decimal(raw).unwrap(), ParsedValue::NegInfinity), "{raw}");
+	}
+	assert!(matches!(parse_decimal("NaN").unwrap(), ParsedValue::Nan));
+}
+
+#[test]
+fn conversion_exceptions_flag_inexact_overflow_underflow_and_invalid() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let flags = |raw: &str, spec: &FloatSpec| {
+		let parsed = parse_decimal(raw).unwrap();
+		conversion_exceptions(&parsed, &parsed_to_softfloat(&parsed, spec, RoundingMode::HalfEven), spec)
+	};
+	assert_eq!(flags("0.1", &fp32), vec![Exception::Inexact]);
+	assert!(flags("0.5", &fp32).is_empty());
+	assert_eq!(flags("1e39", &fp32), vec![Exception::Inexact, Exception::Overflow]);
+	assert_eq!(flags("1e-40", &fp32), vec![Exception::Inexact, Exception::Underflow]);
+	assert!(flags("inf", &fp32).is_empty());
+	let e4m3 = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	assert_eq!(flags("inf", &e4m3), vec![Exception::Invalid]);
+}
+
+#[test]
+fn json_exponent_marks_subnormal_field_as_all_zero() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let report = convert("1e-40", &spec, RoundingMode::HalfEven, 10, Notation::Plain).unwrap();
+	let json: serde_json::Value = serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+	assert_eq!(json["class"], "Subnormal");
+	assert_eq!(json["exponent"]["field"], 0);
+	assert_eq!(json["exponent"]["unbiased"], -126);
+	assert_eq!(json["exponent"]["biased_all_zero"], true);
+	assert_eq!(json["exponent"]["biased_all_ones"], false);
+}
+
+#[test]
+fn hex_case_lower_prints_lowercase_digits() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	let lower = DisplayOptions {
+		hex_case: HexCase::Lower,
+		..display(8, Notation::Plain)
+	};
+	let stored = softfloat_to_rational(&soft, &spec);
+	let report = ConvertReport::new(&soft, &spec, stored.as_ref(), None, RoundingMode::HalfEven, &lower).unwrap();
+	assert_eq!(report.hex, "3fc00000");
+	assert_eq!(describe_softfloat(&soft, &spec, &lower).unwrap(), "1.5 (0x3fc00000)");
+}
+
+#[test]
+fn decoding_fp32_hex_reformats_to_plain_decimal() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let soft = bits_to_softfloat(&hex_to_bits("3FC00000", 32).unwrap(), &spec).unwrap();
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(format_rational(&stored, &display(6, Notation::Plain)), "1.5");
+	assert_eq!(format_rational(&stored, &display(6, Notation::Scientific)), "1.5e+0");
+}
+
+#[test]
+fn custom_bias_shifts_the_exponent_range_and_is_range_checked() {
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let shifted = with_bias(&fp16, 7).unwrap();
+	assert_eq!(min_exponent(&shifted), -6);
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &shifted, RoundingMode::HalfEven);
+	// Exponent field 7 for 2^0: 0 00111 1000000000.
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&soft, &shifted).unwrap()), "1E00");
+	let back = bits_to_softfloat(&softfloat_to_bits(&soft, &shifted).unwrap(), &shifted).unwrap();
+	assert_eq!(softfloat_to_rational(&back, &shifted), Some(parsed_rational("1.5")));
+	assert!(check_strict_ieee(&shifted).is_err());
+
+	// Bias 0: the smallest normal is 2^1, so 1.0 is the subnormal 0 00000 1000000000.
+	let lowest = with_bias(&fp16, 0).unwrap();
+	assert_eq!(min_exponent(&lowest), 1);
+	let one = parsed_to_softfloat(&parse_decimal("1").unwrap(), &lowest, RoundingMode::HalfEven);
+	assert_eq!(one.class, Class::Subnormal);
+	assert_eq!(softfloat_to_bits(&one, &lowest).unwrap(), "0000001000000000");
+	// Bias 31: field 31 is reserved for infinities, so every finite value is below 1.0.
+	let highest = with_bias(&fp16, 31).unwrap();
+	assert_eq!(max_exponent(&highest), -1);
+	let one = parsed_to_softfloat(&parse_decimal("1").unwrap(), &highest, RoundingMode::HalfEven);
+	assert_eq!(one.class, Class::PosInfinity);
+	assert!(with_bias(&fp16, 32).is_err());
+	assert!(with_bias(&fp16, -1).is_err());
+}
+
+#[test]
+fn verilog_output_emits_sized_hex_literal() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).unwrap();
+	assert_eq!(
+		verilog_localparam(&bits, &spec, FieldOrder::default(), HexCase::Upper),
+		"localparam [15:0] VALUE = 16'h3E00; // 0_01111_1000000000"
+	);
+	let tiny = parsed_to_softfloat(&parse_decimal("1e-7").unwrap(), &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&tiny, &spec).unwrap();
+	assert!(verilog_localparam(&bits, &spec, FieldOrder::default(), HexCase::Upper).contains("16'h0002;"));
+}
+
+#[test]
+fn c_output_copies_standard_widths_and_comments_others() {
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &fp32, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &fp32).unwrap();
+	assert_eq!(
+		c_snippet(&bits, &fp32, HexCase::Upper),
+		"const uint32_t bits = 0x3FC00000u; float f; memcpy(&f, &bits, sizeof f);"
+	);
+
+	let bf16 = preset_spec(FormatChoice::Bfloat16).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &bf16, RoundingMode::HalfEven);
+	let snippet = c_snippet(&softfloat_to_bits(&soft, &bf16).unwrap(), &bf16, HexCase::Upper);
+	assert!(snippet.starts_with("const uint16_t bits = 0x3FC0u; /*"), "{snippet}");
+}
+
+#[test]
+fn python_output_uses_struct_numpy_or_a_comment() {
+	let snippet = |raw: &str, choice: FormatChoice| {
+		let spec = preset_spec(choice).unwrap();
+		let soft = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+		python_snippet(&softfloat_to_bits(&soft, &spec).unwrap(), &spec, HexCase::Upper)
+	};
+	assert_eq!(
+		snippet("1.5", FormatChoice::Fp32),
+		"import struct; struct.unpack('<f', struct.pack('<I', 0x3FC00000))[0]"
+	);
+	assert_eq!(
+		snippet("1.5", FormatChoice::Fp64),
+		"import struct; struct.unpack('<d', struct.pack('<Q', 0x3FF8000000000000))[0]"
+	);
+	assert_eq!(
+		snippet("1.5", FormatChoice::Fp16),
+		"import numpy as np; np.array(0x3E00, dtype=np.uint16).view(np.float16)"
+	);
+	assert!(snippet("1.5", FormatChoice::Bfloat16).starts_with("0x3FC0  # "));
+}
+
+#[test]
+fn smallest_fp16_subnormal_has_compact_integer_form() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let soft = bits_to_softfloat(&hex_to_bits("0001", 16).unwrap(), &spec).unwrap();
+	assert_eq!(soft.class, Class::Subnormal);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(integer_times_pow2(&stored).as_deref(), Some("1 × 2^-24"));
+	let soft = bits_to_softfloat(&hex_to_bits("8003", 16).unwrap(), &spec).unwrap();
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	assert_eq!(integer_times_pow2(&stored).as_deref(), Some("-3 × 2^-24"));
+	assert_eq!(integer_times_pow2(&parsed_rational("12")).as_deref(), Some("3 × 2^2"));
+}
+
+#[test]
+fn percentage_input_divides_by_one_hundred_exactly() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let bits = |raw: &str| {
+		let soft = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+		softfloat_to_bits(&soft, &spec).unwrap()
+	};
+	assert_eq!(bits("50%"), bits("0.5"));
+	assert_eq!(parsed_rational("-12.5%"), parsed_rational("-0.125"));
+	assert!(parse_decimal("50%%").is_err());
+	assert!(parse_decimal("5%0").is_err());
+	assert!(parse_decimal("1e2%").is_err());
+	assert!(parse_decimal("inf%").is_err());
+}
+
+#[test]
+fn fp16_exponent_range_spans_fifteen_to_minus_twenty_four() {
+	let range = exponent_range(&preset_spec(FormatChoice::Fp16).unwrap());
+	assert_eq!(
+		range,
+		ExponentRange {
+			max_normal: 15,
+			min_normal: -14,
+			min_subnormal: -24,
+		}
+	);
+	assert_eq!(exponent_range(&preset_spec(FormatChoice::Fp8E4m3).unwrap()).max_normal, 8);
+}
+
+#[test]
+fn bfp_encodes_block_against_shared_exponent() {
+	let layout = BfpLayout::from_str("block=3,mant=8").unwrap();
+	let values: Vec<BigRational> = ["1.0", "0.5", "0.25"].iter().map(|v| parsed_rational(v)).collect();
+	let blocks = encode_bfp(&values, &layout, RoundingMode::HalfEven);
+	assert_eq!(
+		blocks,
+		vec![BfpBlock {
+			shared_exponent: 0,
+			mantissas: vec![BigInt::from(128), BigInt::from(64), BigInt::from(32)],
+		}]
+	);
+
+	// 1.999 rounds to 2^8 at exponent 0, so the block moves up a binade.
+	let layout = BfpLayout::from_str("block=2,mant=8").unwrap();
+	let values: Vec<BigRational> = ["1.999", "-0.3", "0"].iter().map(|v| parsed_rational(v)).collect();
+	let blocks = encode_bfp(&values, &layout, RoundingMode::HalfEven);
+	assert_eq!(blocks[0].shared_exponent, 1);
+	assert_eq!(blocks[0].mantissas, vec![BigInt::from(128), BigInt::from(-19)]);
+	assert_eq!(blocks[1].mantissas, vec![BigInt::zero()]);
+
+	assert!(BfpLayout::from_str("block=0,mant=8").is_err());
+	assert!(BfpLayout::from_str("mant=8").is_err());
+}
+
+#[test]
+fn decimal_to_bits_is_monotonic_over_dense_fp16_sweep() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	// Neighbouring pairs at every binade edge plus a stride through each
+	// binade, probed at the pair, a quarter of the way, just below/at/just
+	// above the midpoint, and just below the upper neighbour. Negatives reuse
+	// the same points mirrored.
+	let value_of = |pattern: u32| {
+		let soft = bits_to_softfloat(&format!("{pattern:016b}"), &spec).unwrap();
+		softfloat_to_rational(&soft, &spec).unwrap()
+	};
+	let epsilon = pow2(-40);
+	let mut samples = Vec::new();
+	for pattern in 0u32..0x7BFF {
+		let fraction = pattern & 0x3FF;
+		let at_edge = !(2..=0x3FD).contains(&fraction);
+		if !at_edge && pattern % 31 != 0 {
+			continue;
+		}
+		let (a, b) = (value_of(pattern), value_of(pattern + 1));
+		let gap = &b - &a;
+		let mid = &a + &gap / BigInt::from(2);
+		samples.push(a.clone());
+		samples.push(&a + &gap / BigInt::from(4));
+		samples.push(&mid - &epsilon);
+		samples.push(mid.clone());
+		samples.push(&mid + &epsilon);
+		samples.push(&b - &epsilon);
+	}
+	samples.push(value_of(0x7BFF));
+	samples.push(parsed_rational("65520"));
+	samples.push(parsed_rational("1e6"));
+	let mut all: Vec<BigRational> = samples.iter().rev().map(|v| -v).collect();
+	all.extend(samples);
+
+	for mode in [RoundingMode::HalfEven, RoundingMode::TowardZero] {
+		let mut previous: Option<(BigInt, &BigRational)> = None;
+		for value in &all {
+			let soft = parsed_to_softfloat(&ParsedValue::Finite(value.clone()), &spec, mode);
+			let ordinal = softfloat_ordinal(&soft, &spec).unwrap().unwrap();
+			if let Some((prev, prev_value)) = &previous {
+				assert!(
+					ordinal >= *prev,
+					"{mode:?}: {prev_value} -> ordinal {prev}, but larger {value} -> ordinal {ordinal}"
+				);
+			}
+			previous = Some((ordinal, value));
+		}
+	}
+}
+
+#[test]
+fn ulps_from_one_counts_ordinals_either_side_of_unity() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let ulps = |hex: &str| {
+		let soft = bits_to_softfloat(&hex_to_bits(hex, 32).unwrap(), &spec).unwrap();
+		ulps_from_one(&soft, &spec).unwrap()
+	};
+	assert_eq!(ulps("3F800001"), Some(BigInt::from(1)));
+	assert_eq!(ulps("3F800000"), Some(BigInt::zero()));
+	assert_eq!(ulps("3F7FFFFF"), Some(BigInt::from(-1)));
+	assert_eq!(ulps("7FC00000"), None);
+}
+
+#[test]
+fn fp80_decode_labels_each_pseudo_encoding() {
+	let spec = preset_spec(FormatChoice::Fp80).unwrap();
+	let decode = |hex: &str| {
+		let bits = hex_to_bits(hex, total_bits(&spec).unwrap()).unwrap();
+		let soft = bits_to_softfloat(&bits, &spec).unwrap();
+		assert_eq!(softfloat_to_bits(&soft, &spec).unwrap(), bits, "{hex} round-trips");
+		soft
+	};
+
+	let one = decode("3FFF8000000000000000");
+	assert_eq!(one.class, Class::Normal);
+	assert_eq!(softfloat_to_rational(&one, &spec), Some(BigRational::one()));
+
+	let unnormal = decode("3FFF4000000000000000");
+	assert_eq!(unnormal.class, Class::Unnormal);
+	assert_eq!(
+		softfloat_to_rational(&unnormal, &spec),
+		Some(BigRational::new(BigInt::one(), BigInt::from(2)))
+	);
+
+	let pseudo_denormal = decode("00008000000000000001");
+	assert_eq!(pseudo_denormal.class, Class::PseudoDenormal);
+	let min_normal = decode("00018000000000000001");
+	assert_eq!(
+		softfloat_to_rational(&pseudo_denormal, &spec),
+		softfloat_to_rational(&min_normal, &spec)
+	);
+
+	assert_eq!(decode("7FFF0000000000000000").class, Class::PseudoInfinity);
+	assert_eq!(decode("FFFF4000000000000000").class, Class::PseudoNan);
+	assert_eq!(decode("7FFF8000000000000000").class, Class::PosInfinity);
+	assert_eq!(decode("7FFFC000000000000000").class, Class::Nan);
+	assert_eq!(decode("00000000000000000001").class, Class::Subnormal);
+}
+
+#[test]
+fn reference_constants_match_canonical_encodings() {
+	let hex_of = |spec: &FloatSpec, name: &str| {
+		let constants = reference_constants(spec, RoundingMode::HalfEven);
+		let (_, soft) = constants.iter().find(|(n, _)| *n == name).unwrap();
+		bits_to_hex(&softfloat_to_bits(soft, spec).unwrap())
+	};
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	assert_eq!(hex_of(&fp32, "Pi"), "40490FDB");
+	assert_eq!(hex_of(&fp32, "Min subnorm"), "1");
+	assert_eq!(hex_of(&fp32, "Max finite"), "7F7FFFFF");
+	let fp64 = preset_spec(FormatChoice::Fp64).unwrap();
+	assert_eq!(hex_of(&fp64, "Pi"), "400921FB54442D18");
+	assert_eq!(hex_of(&fp64, "E"), "4005BF0A8B145769");
+	assert_eq!(hex_of(&fp64, "Sqrt2"), "3FF6A09E667F3BCD");
+}
+
+#[test]
+fn precision_zero_truncates_to_the_integer_part() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let stored = |input: &str| {
+		let soft = parsed_to_softfloat(&parse_decimal(input).unwrap(), &spec, RoundingMode::HalfEven);
+		softfloat_to_rational(&soft, &spec).unwrap()
+	};
+	assert_eq!(format_rational(&stored("0.9"), &display(0, Notation::Plain)), "0");
+	assert_eq!(format_rational(&stored("-0.9"), &display(0, Notation::Plain)), "-0");
+	assert_eq!(format_rational(&stored("12.5"), &display(0, Notation::Plain)), "12");
+	// Scientific notation counts places after the leading digit, so nothing
+	// nonzero collapses to 0.
+	assert_eq!(format_rational(&stored("12.5"), &display(0, Notation::Scientific)), "1e+1");
+	assert_eq!(format_rational(&parsed_rational("0.9"), &display(0, Notation::Scientific)), "9e-1");
+	// FP32's 0.9 is 0.89999997615814208984375, truncated rather than rounded.
+	assert_eq!(format_rational(&stored("0.9"), &display(0, Notation::Scientific)), "8e-1");
+	assert_eq!(format_rational(&parsed_rational("0.05"), &display(1, Notation::Scientific)), "5e-2");
+	assert_eq!(format_rational(&parsed_rational("-0.0000012"), &display(6, Notation::Scientific)), "-1.2e-6");
+	assert_eq!(format_rational(&parsed_rational("1e-1000000"), &display(3, Notation::Scientific)), "1e-1000000");
+}
+
+#[test]
+fn significand_first_field_order_round_trips_fp16() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let order: FieldOrder = "frac,exp,sign".parse().unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	let standard = softfloat_to_bits(&soft, &spec).unwrap();
+	let packed = order.pack(&standard, &spec);
+	assert_eq!(packed, "1000000000011110");
+	assert_eq!(bits_to_hex(&packed), "801E");
+
+	let unpacked = order.unpack(&packed, &spec).unwrap();
+	assert_eq!(unpacked, standard);
+	let decoded = bits_to_softfloat(&unpacked, &spec).unwrap();
+	assert_eq!(softfloat_to_rational(&decoded, &spec), Some(BigRational::new(BigInt::from(3), BigInt::from(2))));
+	assert_eq!(
+		verilog_localparam(&standard, &spec, order, HexCase::Upper),
+		"localparam [15:0] VALUE = 16'h801E; // 1000000000_01111_0"
+	);
+
+	assert!("sign,exp".parse::<FieldOrder>().is_err());
+	assert!("sign,exp,exp".parse::<FieldOrder>().is_err());
+}
+
+#[test]
+fn rounding_carry_reports_an_exponent_increment() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let carries = |input: &str, rounding| rounding_carries(&parsed_rational(input), &spec, rounding);
+	// Just below 2: the all-ones fraction rounds up to 2.0 = 1.0 × 2^1.
+	assert!(carries("1.9999", RoundingMode::HalfEven));
+	let soft = parsed_to_softfloat(&parse_decimal("1.9999").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!((soft.exponent, soft.significand.is_zero()), (1, true));
+	assert!(!carries("1.9999", RoundingMode::TowardZero));
+	assert!(!carries("1.5", RoundingMode::HalfEven));
+	// Just below the smallest normal 2^-14: a subnormal rounds up into the normal range.
+	assert!(carries("0.0000610351", RoundingMode::HalfEven));
+	let soft = parsed_to_softfloat(&parse_decimal("0.0000610351").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(soft.class, Class::Normal);
+	let report = |input: &str, rounding| convert(input, &spec, rounding, 4, Notation::Plain).unwrap();
+	assert_eq!(report("1.9999", RoundingMode::HalfEven).rounding_carry, Some(true));
+	assert_eq!(report("1.9999", RoundingMode::TowardZero).rounding_carry, Some(false));
+	assert_eq!(report("inf", RoundingMode::HalfEven).rounding_carry, None);
+}
+
+#[test]
+fn engine_agrees_with_the_platform_parser_on_random_fp32_decimals() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let mut state = 0x9E37_79B9_7F4A_7C15u64;
+	let mut next = move || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+	for _ in 0..2000 {
+		let digits = next() % 1_000_000_000_000;
+		let exponent = (next() % 90) as i64 - 50;
+		let sign = if next() % 2 == 0 { "" } else { "-" };
+		let raw = format!("{sign}{}.{:012}e{exponent}", next() % 10, digits);
+		let comparison = compare_native(&raw, &spec, RoundingMode::HalfEven).unwrap();
+		assert_eq!(comparison.ulps, Some(BigUint::zero()), "{raw}");
+	}
+	// Spellings Rust's parser rejects still reach it as plain decimals.
+	for raw in ["1_000", "50%", "-0.125e-3", "inf"] {
+		let comparison = compare_native(raw, &spec, RoundingMode::HalfEven).unwrap();
+		assert_eq!(comparison.ulps, Some(BigUint::zero()), "{raw}");
+	}
+}
+
+#[test]
+fn continued_fraction_of_fp16_tenth() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("0.1").unwrap(), &spec, RoundingMode::HalfEven);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	// 0.0999755859375 = 819/8192
+	assert_eq!(stored, BigRational::new(BigInt::from(819), BigInt::from(8192)));
+	let terms = continued_fraction(&stored);
+	assert_eq!(terms, [0, 10, 409, 2].map(BigInt::from));
+	assert_eq!(format_continued_fraction(&terms), "[0; 10, 409, 2]");
+	assert_eq!(format_continued_fraction(&continued_fraction(&parsed_rational("-1.5"))), "[-2; 2]");
+	assert_eq!(format_continued_fraction(&continued_fraction(&parsed_rational("3"))), "[3]");
+	assert_eq!(format_continued_fraction(&[]), "");
+}
+
+#[test]
+fn exact_powers_of_two_encode_with_a_zero_fraction() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	for rounding in [RoundingMode::HalfEven, RoundingMode::TowardZero] {
+		for (input, exponent, hex) in [
+			("2.0", 1, "4000"),
+			("0.5", -1, "3800"),
+			("32768", 15, "7800"),
+			("-32768", 15, "F800"),
+			("0.00006103515625", -14, "400"),
+		] {
+			let value = parsed_rational(input);
+			let soft = parsed_to_softfloat(&ParsedValue::Finite(value.clone()), &spec, rounding);
+			assert_eq!(soft.class, Class::Normal, "{input}");
+			assert_eq!(soft.exponent, exponent, "{input}");
+			assert!(soft.significand.is_zero(), "{input}");
+			assert!(!rounding_carries(&value, &spec, rounding), "{input}");
+			assert!(!is_exactly_halfway(&value, &spec), "{input}");
+			assert_eq!(bits_to_hex(&softfloat_to_bits(&soft, &spec).unwrap()), hex, "{input}");
+			assert_eq!(softfloat_to_rational(&soft, &spec), Some(value));
+		}
+	}
+}
+
+#[test]
+fn min_format_counts_significant_and_exponent_bits() {
+	let min = |input: &str| min_format(&parsed_rational(input));
+	assert_eq!(
+		min("0.75"),
+		Some(MinFormat {
+			significant_bits: 2,
+			exponent_bits: 3
+		})
+	);
+	assert_eq!(
+		min("1"),
+		Some(MinFormat {
+			significant_bits: 1,
+			exponent_bits: 2
+		})
+	);
+	// 65504 = 2047 × 2^5 is FP16's largest normal: 11 bits, exponent 15.
+	assert_eq!(
+		min("-65504"),
+		Some(MinFormat {
+			significant_bits: 11,
+			exponent_bits: 5
+		})
+	);
+	assert_eq!(min("0.1"), None);
+}
+
+#[test]
+fn fp24_encodes_one_and_a_half_in_six_nibbles() {
+	let spec = preset_spec(FormatChoice::Fp24).unwrap();
+	assert_eq!(total_bits(&spec).unwrap(), 24);
+	let soft = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	let bits = softfloat_to_bits(&soft, &spec).unwrap();
+	assert_eq!(bits, "001111111000000000000000");
+	assert_eq!(bits_to_hex(&bits), "3F8000");
+	assert_eq!(bits_to_hex_bytes(&bits), "3F 80 00");
+	let decoded = bits_to_softfloat(&hex_to_bits("3F8000", 24).unwrap(), &spec).unwrap();
+	assert_eq!(softfloat_to_rational(&decoded, &spec), Some(BigRational::new(BigInt::from(3), BigInt::from(2))));
+}
+
+#[test]
+fn special_value_policies_decide_the_all_ones_exponent() {
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let decode = |spec: &FloatSpec, hex: &str| bits_to_softfloat(&hex_to_bits(hex, 16).unwrap(), spec).unwrap();
+	let encode = |spec: &FloatSpec, input: &str| {
+		let soft = parsed_to_softfloat(&parse_decimal(input).unwrap(), spec, RoundingMode::HalfEven);
+		softfloat_to_bits(&soft, spec).map(|bits| bits_to_hex(&bits))
+	};
+
+	// IEEE: the all-ones exponent is infinity or NaN.
+	assert_eq!(decode(&fp16, "7C00").class, Class::PosInfinity);
+	assert_eq!(decode(&fp16, "7C01").class, Class::Nan);
+	assert_eq!(encode(&fp16, "1e6").unwrap(), "7C00");
+
+	// No infinities, NaN only at all ones: the top binade extends the range.
+	let fn16 = with_specials(&fp16, false, NanPolicy::AllOnesPattern).unwrap();
+	assert_eq!(decode(&fn16, "7C00").class, Class::Normal);
+	assert_eq!(softfloat_to_rational(&decode(&fn16, "7C00"), &fn16), Some(BigRational::from_integer(BigInt::from(65536))));
+	assert_eq!(decode(&fn16, "7FFF").class, Class::Nan);
+	assert_eq!(encode(&fn16, "1e6").unwrap(), "7FFF");
+	assert_eq!(encode(&fn16, "inf").unwrap(), "7FFF");
+
+	// No infinities, every all-ones-exponent pattern is NaN.
+	let nan16 = with_specials(&fp16, false, NanPolicy::AllOnesExponent).unwrap();
+	assert_eq!(decode(&nan16, "7C00").class, Class::Nan);
+	assert_eq!(encode(&nan16, "65504").unwrap(), "7BFF");
+
+	// Neither: every pattern is finite and overflow saturates.
+	let finite16 = with_specials(&fp16, false, NanPolicy::None).unwrap();
+	assert_eq!(decode(&finite16, "7FFF").class, Class::Normal);
+	assert_eq!(encode(&finite16, "-1e6").unwrap(), "FFFF");
+	assert!(encode(&finite16, "nan").is_err());
+
+	assert!(with_specials(&fp16, true, NanPolicy::AllOnesPattern).is_err());
+	assert!(check_strict_ieee(&nan16).is_err());
+}
+
+#[test]
+fn decode_packed_splits_a_blob_of_fp16_values() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let values = |blob: &[u8], endianness| -> Vec<Option<BigRational>> {
+		decode_packed(blob, &spec, endianness, FieldOrder::default())
+			.unwrap()
+			.iter()
+			.map(|soft| softfloat_to_rational(soft, &spec))
+			.collect()
+	};
+	let expected = vec![Some(BigRational::one()), Some(BigRational::from_integer(BigInt::from(-2)))];
+	assert_eq!(values(&[0x3C, 0x00, 0xC0, 0x00], Endianness::Big), expected);
+	assert_eq!(values(&[0x00, 0x3C, 0x00, 0xC0], Endianness::Little), expected);
+	assert!(decode_packed(&[0x3C, 0x00, 0xC0], &spec, Endianness::Big, FieldOrder::default()).is_err());
+}
+
+#[test]
+fn encode_packed_writes_little_endian_fp16() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let values: Vec<SoftFloat> = ["1.0", "2.0"]
+		.iter()
+		.map(|raw| parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven))
+		.collect();
+	let bytes = encode_packed(&values, &spec, Endianness::Little, FieldOrder::default()).unwrap();
+	assert_eq!(bytes, [0x00, 0x3C, 0x00, 0x40]);
+	assert_eq!(encode_packed(&values, &spec, Endianness::Big, FieldOrder::default()).unwrap(), [0x3C, 0x00, 0x40, 0x00]);
+	let decoded = decode_packed(&bytes, &spec, Endianness::Little, FieldOrder::default()).unwrap();
+	assert_eq!(softfloat_to_bits(&decoded[1], &spec).unwrap(), softfloat_to_bits(&values[1], &spec).unwrap());
+	// 1.0 is 0 01111 0000000000; with the sign last it packs as 0x7800.
+	let swapped: FieldOrder = "exp,frac,sign".parse().unwrap();
+	let bytes = encode_packed(&values[..1], &spec, Endianness::Big, swapped).unwrap();
+	assert_eq!(bytes, [0x78, 0x00]);
+	let decoded = decode_packed(&bytes, &spec, Endianness::Big, swapped).unwrap();
+	assert_eq!(softfloat_to_rational(&decoded[0], &spec), Some(BigRational::one()));
+}
+
+#[test]
+fn require_exact_rejects_values_that_would_round() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let exact = |raw: &str| parsed_to_softfloat_exact(&parse_decimal(raw).unwrap(), &spec);
+	let half = exact("0.5").unwrap();
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&half, &spec).unwrap()), "3F000000");
+	assert!(exact("0.1").is_err());
+	assert!(exact("1e39").is_err());
+	assert!(exact("-inf").unwrap().class.is_infinite());
+	let e4m3 = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	assert!(parsed_to_softfloat_exact(&parse_decimal("inf").unwrap(), &e4m3).is_err());
+}
+
+#[test]
+fn assembles_fields_without_rounding() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let field = |raw: &str| parse_field_value(raw).unwrap();
+	let bits = assemble_fields(&field("0"), &field("127"), &field("0x400000"), &spec).unwrap();
+	assert_eq!(bits_to_hex(&bits), "3FC00000");
+	let value = softfloat_to_rational(&bits_to_softfloat(&bits, &spec).unwrap(), &spec).unwrap();
+	assert_eq!(value, parsed_rational("1.5"));
+	assert!(assemble_fields(&field("2"), &field("127"), &field("0"), &spec).is_err());
+	assert!(assemble_fields(&field("0"), &field("256"), &field("0"), &spec).is_err());
+	assert!(assemble_fields(&field("0"), &field("0"), &field("0x800000"), &spec).is_err());
+	let fp80 = preset_spec(FormatChoice::Fp80).unwrap();
+	let one = assemble_fields(&field("0"), &field("16383"), &field("0x8000000000000000"), &fp80).unwrap();
+	assert_eq!(bits_to_hex(&one), "3FFF8000000000000000");
+}
+
+#[test]
+fn midpoints_bracket_the_rounding_interval() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let at = |raw: &str| {
+		let sf = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+		rounding_midpoints(&sf, &spec).unwrap().unwrap()
+	};
+	let (lower, upper) = at("1.0");
+	assert_eq!(lower, parsed_rational("0.999755859375"));
+	assert_eq!(upper, parsed_rational("1.00048828125"));
+	let (lower, upper) = at("0");
+	assert_eq!(lower, -parsed_rational("0.0000000298023223876953125"));
+	assert_eq!(upper, parsed_rational("0.0000000298023223876953125"));
+	let (_, upper) = at("65504");
+	assert_eq!(upper, parsed_rational("65520"));
+	let inf = parsed_to_softfloat(&ParsedValue::PosInfinity, &spec, RoundingMode::HalfEven);
+	assert!(rounding_midpoints(&inf, &spec).unwrap().is_none());
+}
+
+#[test]
+fn extreme_decimal_exponents_short_circuit() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let convert = |raw: &str, rounding| {
+		let sf = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, rounding);
+		bits_to_hex(&softfloat_to_bits(&sf, &spec).unwrap())
+	};
+	assert_eq!(convert("1e100000", RoundingMode::HalfEven), "7F800000");
+	assert_eq!(convert("-1e100000", RoundingMode::HalfEven), "FF800000");
+	assert_eq!(convert("1e-100000", RoundingMode::HalfEven), "0");
+	assert_eq!(convert("-1e-100000", RoundingMode::HalfEven), "80000000");
+	assert_eq!(convert("1e100000", RoundingMode::TowardZero), "7F800000");
+	// Exponents this large used to be scaled out in full before rounding.
+	assert_eq!(convert("1e1000000", RoundingMode::HalfEven), "7F800000");
+	assert_eq!(convert("-1e1000000", RoundingMode::HalfEven), "FF800000");
+	assert_eq!(convert("1e-1000000", RoundingMode::HalfEven), "0");
+	assert_eq!(convert("-1e-1000000", RoundingMode::TowardZero), "80000000");
+	assert_eq!(convert("0e-1000000", RoundingMode::HalfEven), "0");
+	// Just under half the smallest subnormal still rounds to zero, and just over it up.
+	assert_eq!(convert("7.006e-46", RoundingMode::HalfEven), "0");
+	assert_eq!(convert("7.007e-46", RoundingMode::HalfEven), "1");
+}
+
+#[test]
+fn extreme_decimals_keep_their_exact_value() {
+	let tiny = [parsed_rational("1e-20000"), parsed_rational("3e-20000")];
+	assert_eq!(format_fraction(&tiny[0]), format!("1/1{}", "0".repeat(20000)));
+	assert_eq!(format_fraction(&tiny[1]), format!("3/1{}", "0".repeat(20000)));
+	let layout = BfpLayout::from_str("block=2,mant=4").unwrap();
+	let blocks = encode_bfp(&tiny, &layout, RoundingMode::HalfEven);
+	assert_ne!(blocks[0].mantissas[0], blocks[0].mantissas[1]);
+	let huge = [parsed_rational("1e20000"), parsed_rational("3e20000")];
+	let blocks = encode_bfp(&huge, &layout, RoundingMode::HalfEven);
+	assert_ne!(blocks[0].mantissas[0], blocks[0].mantissas[1]);
+	// The error against an underflowed input is the input itself, undiminished.
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let report = convert("1e-1000000", &spec, RoundingMode::HalfEven, 4, Notation::Plain).unwrap();
+	assert_eq!(report.error.as_deref(), Some("-0.0000"));
+	assert_eq!(parsed_rational("50%"), parsed_rational("0.5"));
+	assert!(parse_decimal("1e1000001").is_err());
+	assert!(parse_decimal("1e-99999999999").is_err());
+}
+
+#[test]
+fn implicit_significand_shows_the_leading_bit() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let sf = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(implicit_significand(&sf, &spec).unwrap(), format!("1.1{}₂", "0".repeat(22)));
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&sf, &spec).unwrap()), "3FC00000");
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let tiny = bits_to_softfloat(&hex_to_bits("0001", 16).unwrap(), &fp16).unwrap();
+	assert_eq!(implicit_significand(&tiny, &fp16).unwrap(), "0.0000000001₂");
+	let inf = parsed_to_softfloat(&ParsedValue::PosInfinity, &fp16, RoundingMode::HalfEven);
+	assert!(implicit_significand(&inf, &fp16).is_none());
+}
+
+#[test]
+fn specials_dump_covers_the_format_landmarks() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let dump: Vec<(&str, Option<String>)> = special_values(&spec)
+		.into_iter()
+		.map(|(name, soft)| (name, soft.map(|sf| bits_to_hex(&softfloat_to_bits(&sf, &spec).unwrap()))))
+		.collect();
+	let hex = |name: &str| dump.iter().find(|(n, _)| *n == name).unwrap().1.clone().unwrap();
+	assert_eq!(hex("+0"), "0");
+	assert_eq!(hex("-0"), "8000");
+	assert_eq!(hex("Min subnorm"), "1");
+	assert_eq!(hex("Max subnorm"), "3FF");
+	assert_eq!(hex("Min normal"), "400");
+	assert_eq!(hex("Max normal"), "7BFF");
+	assert_eq!(hex("+Inf"), "7C00");
+	assert_eq!(hex("-Inf"), "FC00");
+	assert_eq!(hex("NaN"), "7E00");
+	let max = special_values(&spec).into_iter().find(|(n, _)| *n == "Max normal").unwrap().1.unwrap();
+	assert_eq!(softfloat_to_rational(&max, &spec).unwrap(), parsed_rational("65504"));
+
+	let e4m3 = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	let specials = special_values(&e4m3);
+	assert!(specials.iter().find(|(n, _)| *n == "+Inf").unwrap().1.is_none());
+	let max = specials.iter().find(|(n, _)| *n == "Max normal").unwrap().1.clone().unwrap();
+	assert_eq!(softfloat_to_rational(&max, &e4m3).unwrap(), parsed_rational("448"));
+}
+
+#[test]
+fn complex_parts_quantize_independently() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let (real, imag) = parse_complex("1.5+2.5i").unwrap();
+	let quantized = quantize_complex(&real, &imag, &spec, RoundingMode::HalfEven);
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&quantized.real, &spec).unwrap()), "3E00");
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&quantized.imag, &spec).unwrap()), "4100");
+	assert!(quantized.error_squared.unwrap().is_zero());
+
+	let finite = |value: &ParsedValue| match value {
+		ParsedValue::Finite(v) => v.clone(),
+		other => panic!("expected a finite part, got {other:?}"),
+	};
+	let (real, imag) = parse_complex("-1e-3-2E+2i").unwrap();
+	assert_eq!(finite(&real), parsed_rational("-0.001"));
+	assert_eq!(finite(&imag), parsed_rational("-200"));
+	let (_, imag) = parse_complex("3-i").unwrap();
+	assert_eq!(finite(&imag), parsed_rational("-1"));
+	let quantized = quantize_complex(&real, &ParsedValue::Finite(parsed_rational("0")), &spec, RoundingMode::HalfEven);
+	assert!(quantized.error_squared.unwrap().is_positive());
+	assert!(parse_complex("1.5").is_err());
+	assert!(parse_complex("2.5i").is_err());
+}
+
+#[test]
+fn binade_values_cover_one_exponent() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let values: Vec<BigRational> = values_in_binade(0, &spec).collect();
+	assert_eq!(values.len(), 1024);
+	assert_eq!(values[0], parsed_rational("1"));
+	assert_eq!(values[1023], parsed_rational("1.9990234375"));
+	assert!(values.windows(2).all(|pair| &pair[1] - &pair[0] == parsed_rational("0.0009765625")));
+	assert_eq!(values_in_binade(16, &spec).count(), 0);
+	assert_eq!(values_in_binade(-15, &spec).count(), 0);
+
+	// The top E4M3 binade loses its last encoding to NaN.
+	let e4m3 = preset_spec(FormatChoice::Fp8E4m3).unwrap();
+	let top: Vec<BigRational> = values_in_binade(8, &e4m3).collect();
+	assert_eq!(top.len(), 7);
+	assert_eq!(top[6], parsed_rational("448"));
+}
+
+#[test]
+fn total_order_key_separates_signed_zeros_and_nans() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let key = |hex: &str| total_order_key(&bits_to_softfloat(&hex_to_bits(hex, 16).unwrap(), &spec).unwrap(), &spec).unwrap();
+	let ordered = ["FE00", "FC00", "BC00", "8001", "8000", "0000", "0001", "3C00", "7C00", "7E00"];
+	assert!(ordered.windows(2).all(|pair| key(pair[0]) < key(pair[1])));
+}
+
+#[test]
+fn ulp_value_prints_exactly() {
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let fp32 = preset_spec(FormatChoice::Fp32).unwrap();
+	let ulp = |raw: &str, spec: &FloatSpec| format_rational(&ulp_gap(&parsed_rational(raw), spec), &display(40, Notation::Plain));
+	assert_eq!(ulp("1", &fp16), "0.0009765625");
+	assert_eq!(ulp("1", &fp32), "0.00000011920928955078125");
+	// Subnormals and zero share the spacing of the smallest normal binade.
+	assert_eq!(ulp("0", &fp16), ulp("0.00006103515625", &fp16));
+	assert_eq!(ulp("0", &fp16), "0.000000059604644775390625");
+}
+
+/// Decodes an OCP MX E8M0 scale: 8 unsigned exponent bits with no fraction
+/// and no zero. `FF` is NaN and every other pattern `e` is `2^(e - 127)`.
+fn decode_e8m0(bits: &str) -> Result<ParsedValue> {
+	let cleaned = strip_binary_prefix(bits);
+	if cleaned.is_empty() {
+		return Err(empty_input("bits"));
+	}
+	if cleaned.len() != 8 {
+		bail!("expected 8 bits for an E8M0 scale, got {}", cleaned.len());
+	}
+	// The same fields behind a clear sign bit; only the all-zero row differs,
+	// being 2^-127 rather than zero.
+	let signed = FloatSpec {
+		name: "E8M0",
+		exponent_bits: 8,
+		significand_bits: 0,
+		has_infinity: false,
+		nan_encoding: NanPolicy::AllOnesPattern,
+		bias: 127,
+		explicit_integer_bit: false,
 	};
-	let parsed = parse_decimal("0.1").expect("parse decimal");
-	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
-	let bits = softfloat_to_bits(&soft, &spec);
-	assert_eq!(bits, "00111101110011001100110011001101");
+	let soft = bits_to_softfloat(&format!("0{cleaned}"), &signed)?;
+	Ok(match soft.class {
+		Class::Nan => ParsedValue::Nan,
+		Class::Zero => ParsedValue::Finite(pow2(-127)),
+		_ => ParsedValue::Finite(
+			softfloat_to_rational(&soft, &signed).expect("finite classes have a rational value"),
+		),
+	})
 }
 
 #[test]
-fn decimal_negative_two_point_five_matches_reference_bits() {
-	let spec = FloatSpec {
-		name: "FP32",
+fn decodes_formats_without_a_fraction_field() {
+	let e8m0 = |hex: &str| decode_e8m0(&hex_to_bits(hex, 8).unwrap()).unwrap();
+	let finite = |hex: &str| match e8m0(hex) {
+		ParsedValue::Finite(v) => v,
+		other => panic!("{hex}: expected a finite scale, got {other:?}"),
+	};
+	assert_eq!(finite("7F"), parsed_rational("1"));
+	assert_eq!(finite("7D"), parsed_rational("0.25"));
+	assert_eq!(finite("FE"), BigRational::from_integer(BigInt::one() << 127));
+	// E8M0 has no zero: the all-zero pattern is the smallest scale.
+	assert_eq!(finite("00"), BigRational::new(BigInt::one(), BigInt::one() << 127));
+	assert!(matches!(e8m0("FF"), ParsedValue::Nan));
+	assert!(decode_e8m0("001111111").is_err());
+
+	// NaN handling must not reach for a quiet bit the format does not have.
+	let fractionless = FloatSpec {
+		name: "S1E8M0",
 		exponent_bits: 8,
-		significand_bits: 23,
+		significand_bits: 0,
+		has_infinity: false,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 127,
+		explicit_integer_bit: false,
 	};
-	let parsed = parse_decimal("-2.5").expect("parse decimal");
-	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
-	let bits = softfloat_to_bits(&soft, &spec);
-	assert_eq!(bits, "11000000001000000000000000000000");
+	let nan = canonical_nan(&fractionless);
+	assert_eq!(softfloat_to_bits(&nan, &fractionless).unwrap(), "011111111");
+	assert!(matches!(softfloat_to_value(&nan, &fractionless), ValueOrSpecial::Nan { quiet: true, .. }));
+	let one = bits_to_softfloat("001111111", &fractionless).unwrap();
+	let (product, _) = mul_softfloats(&nan, &one, &fractionless, RoundingMode::HalfEven);
+	assert_eq!(product.class, Class::Nan);
 }
 
 #[test]
-fn fp16_one_point_five_matches_reference_bits() {
-	let spec = FloatSpec {
-		name: "FP16",
-		exponent_bits: 5,
-		significand_bits: 10,
+fn arithmetic_follows_ieee_signed_zero_rules() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let operand = |raw: &str| decimal_operand(raw, &spec, RoundingMode::HalfEven, &display(32, Notation::Plain)).unwrap();
+	let hex = |sf: &SoftFloat| bits_to_hex(&softfloat_to_bits(sf, &spec).unwrap());
+	assert!(operand("-0").sign);
+
+	let (sum, _) = two_sum_softfloats(&operand("-0"), &operand("-0"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&sum), "80000000");
+	let (sum, _) = two_sum_softfloats(&operand("-0"), &operand("0"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&sum), "0");
+	let (sum, _) = two_sum_softfloats(&operand("1"), &operand("-1"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&sum), "0");
+
+	let (product, _) = mul_softfloats(&operand("-3"), &operand("0"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&product), "80000000");
+	let (product, _) = mul_softfloats(&operand("-3"), &operand("-0"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&product), "0");
+	let rem = remainder_softfloats(&operand("-4"), &operand("2"), &spec, RoundingMode::HalfEven, QuotientRounding::NearestEven);
+	assert_eq!(hex(&rem), "80000000");
+
+	let (product, _) = mul_softfloats(&operand("-3"), &operand("0"), &spec, RoundingMode::HalfEven);
+	assert_eq!(hex(&normalize_minus_zero(product)), "0");
+}
+
+#[test]
+fn trailing_zero_bits_count_the_fraction_field() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let zeros = |raw: &str| {
+		let sf = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+		trailing_zero_bits(&sf, &spec)
 	};
-	let parsed = parse_decimal("1.5").expect("parse decimal");
-	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
-	let bits = softfloat_to_bits(&soft, &spec);
-	assert_eq!(bits, "0011111000000000");
+	assert_eq!(zeros("1.5"), Some(22));
+	assert_eq!(zeros("1.25"), Some(21));
+	assert_eq!(zeros("8"), Some(23));
+	assert_eq!(zeros("0.1"), Some(0));
+	assert_eq!(zeros("inf"), None);
 }
 
 #[test]
-fn bfloat16_pi_matches_reference_bits() {
-	let spec = FloatSpec {
-		name: "bfloat16",
-		exponent_bits: 8,
-		significand_bits: 7,
+fn significand_hex_includes_the_implicit_bit() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let sf = parsed_to_softfloat(&parse_decimal("1.5").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(significand_hex(&sf, &spec).unwrap(), "0xC00000");
+	assert_eq!(sf.significand, BigUint::from(0x400000u32));
+	let fp16 = preset_spec(FormatChoice::Fp16).unwrap();
+	let one = parsed_to_softfloat(&parse_decimal("1").unwrap(), &fp16, RoundingMode::HalfEven);
+	assert_eq!(significand_hex(&one, &fp16).unwrap(), "0x400");
+	let tiny = bits_to_softfloat(&hex_to_bits("0001", 16).unwrap(), &fp16).unwrap();
+	assert_eq!(significand_hex(&tiny, &fp16).unwrap(), "0x001");
+}
+
+#[test]
+fn ulp_grid_lists_neighbours_in_order() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let one = parsed_to_softfloat(&parse_decimal("1.0").unwrap(), &spec, RoundingMode::HalfEven);
+	let grid = ulp_grid(&one, 2, &spec).unwrap().unwrap();
+	let offsets: Vec<i64> = grid.iter().map(|(offset, _)| *offset).collect();
+	assert_eq!(offsets, [-2, -1, 0, 1, 2]);
+	let values: Vec<BigRational> = grid.iter().map(|(_, sf)| softfloat_to_rational(sf, &spec).unwrap()).collect();
+	let expected = ["0.9990234375", "0.99951171875", "1", "1.0009765625", "1.001953125"];
+	assert_eq!(values, expected.map(parsed_rational));
+
+	// Past the largest finite value the grid reaches infinity, then stops.
+	let max = parsed_to_softfloat(&parse_decimal("65504").unwrap(), &spec, RoundingMode::HalfEven);
+	let grid = ulp_grid(&max, 2, &spec).unwrap().unwrap();
+	assert_eq!(grid.len(), 4);
+	assert!(grid[3].1.class.is_infinite());
+}
+
+#[test]
+fn empty_input_is_reported_as_such() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let message = |err: anyhow::Error| err.to_string();
+	assert_eq!(message(bits_to_softfloat("", &spec).unwrap_err()), "empty input: expected bits");
+	assert_eq!(message(bits_to_softfloat(" 0b ", &spec).unwrap_err()), "empty input: expected bits");
+	assert_eq!(message(FieldOrder::default().unpack("", &spec).unwrap_err()), "empty input: expected bits");
+	assert_eq!(message(hex_to_bits("", 32).unwrap_err()), "empty input: expected hex digits");
+	assert_eq!(message(hex_to_bits("0x", 32).unwrap_err()), "empty input: expected hex digits");
+	assert_eq!(message(parse_decimal("   ").unwrap_err()), "empty input: expected a decimal");
+	assert_eq!(message(parse_field_value("").unwrap_err()), "empty input: expected a field value");
+	assert_eq!(message(parse_complex("").unwrap_err()), "empty input: expected a complex a+bi");
+	assert_eq!(
+		message(decode_packed(&[], &spec, Endianness::Little, FieldOrder::default()).unwrap_err()),
+		"empty input: expected packed binary data"
+	);
+}
+
+#[test]
+fn baseline_difference_from_the_input_is_the_conversion_error() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let source = parsed_rational("0.1");
+	let soft = parsed_to_softfloat(&ParsedValue::Finite(source.clone()), &spec, RoundingMode::HalfEven);
+	let stored = softfloat_to_rational(&soft, &spec).unwrap();
+	let (diff, ulps) = baseline_difference(&stored, &source, &spec);
+	let report = convert("0.1", &spec, RoundingMode::HalfEven, 40, Notation::Plain).unwrap();
+	assert_eq!(report.error.as_deref(), Some(format_rational(&diff, &display(40, Notation::Plain)).as_str()));
+	assert_eq!(ulps, error_in_ulps(&diff, &stored, &spec).2);
+
+	let (diff, ulps) = baseline_difference(&stored, &parsed_rational("0.0999755859375"), &spec);
+	assert_eq!(diff, BigRational::zero());
+	assert_eq!(ulps, BigRational::zero());
+}
+
+#[test]
+fn monotone_key_orders_like_unsigned_integers() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let key = |raw: &str| {
+		let soft = parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+		bits_to_hex(&monotone_key(&soft, &spec).unwrap())
 	};
-	let parsed = parse_decimal("3.14159265").expect("parse decimal");
-	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
-	let bits = softfloat_to_bits(&soft, &spec);
-	assert_eq!(bits, "0100000001001001");
+	assert_eq!(key("1.0"), "BF800000");
+	assert_eq!(key("-1.0"), "407FFFFF");
+	let value = |hex: String| u32::from_str_radix(&hex, 16).unwrap();
+	assert!(value(key("-1.0")) < value(key("1.0")));
+	assert!(value(key("-inf")) < value(key("-1.0")));
+	assert!(value(key("0")) < value(key("1e-45")));
 }
 
 #[test]
-fn fp64_negative_value_matches_reference_bits() {
-	let spec = FloatSpec {
-		name: "FP64",
-		exponent_bits: 11,
-		significand_bits: 52,
+fn one_bit_exponent_has_only_subnormals_and_specials() {
+	let spec = custom_spec(1, 2).unwrap();
+	assert_eq!(spec.bias, 0);
+	let decoded: Vec<_> = (0..8u8)
+		.map(|pattern| {
+			let soft = bits_to_softfloat(&format!("{pattern:04b}"), &spec).unwrap();
+			(soft.class.clone(), softfloat_to_rational(&soft, &spec))
+		})
+		.collect();
+	let half = |n: i64| Some(BigRational::new(BigInt::from(n), BigInt::from(2)));
+	assert_eq!(
+		decoded,
+		vec![
+			(Class::Zero, half(0)),
+			(Class::Subnormal, half(1)),
+			(Class::Subnormal, half(2)),
+			(Class::Subnormal, half(3)),
+			(Class::PosInfinity, None),
+			(Class::Nan, None),
+			(Class::Nan, None),
+			(Class::Nan, None),
+		]
+	);
+	let round = |raw: &str| parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven).class;
+	assert_eq!(round("1.6"), Class::Subnormal);
+	assert_eq!(round("1.75"), Class::PosInfinity);
+	assert_eq!(exponent_range(&spec).min_normal, 1);
+	assert!(special_values(&spec).iter().any(|(name, value)| *name == "Max normal" && value.is_none()));
+}
+
+#[test]
+fn reclaimed_all_ones_exponent_is_a_normal_binade() {
+	let spec = with_specials(&preset_spec(FormatChoice::Fp16).unwrap(), false, NanPolicy::None).unwrap();
+	let soft = parsed_to_softfloat(&parse_decimal("100000").unwrap(), &spec, RoundingMode::HalfEven);
+	assert_eq!(soft.class, Class::Normal);
+	assert_eq!(soft.exponent, 16);
+	let bits = softfloat_to_bits(&soft, &spec).unwrap();
+	assert_eq!(bits_to_hex(&bits), "7E1A");
+	assert_eq!(softfloat_to_rational(&soft, &spec), Some(parsed_rational("99968")));
+
+	let top = bits_to_softfloat(&hex_to_bits("7FFF", 16).unwrap(), &spec).unwrap();
+	assert_eq!(top.class, Class::Normal);
+	assert_eq!(softfloat_to_rational(&top, &spec), Some(parsed_rational("131008")));
+	let saturated = parsed_to_softfloat(&ParsedValue::PosInfinity, &spec, RoundingMode::HalfEven);
+	assert_eq!(softfloat_to_bits(&saturated, &spec).unwrap(), softfloat_to_bits(&top, &spec).unwrap());
+}
+
+#[test]
+fn batch_summary_counts_exceptions() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let mut summary = BatchSummary::default();
+	for raw in ["0.5", "0.1", "1e10", "1e-6", "nan"] {
+		let source = parse_decimal(raw).unwrap();
+		let result = parsed_to_softfloat(&source, &spec, RoundingMode::HalfEven);
+		summary.record(&source, &result, &spec);
+	}
+	assert_eq!(summary.total, 5);
+	assert_eq!(summary.inexact, 3);
+	assert_eq!(summary.overflow, 1);
+	assert_eq!(summary.underflow, 1);
+	assert_eq!(summary.subnormal, 1);
+	assert_eq!(summary.max_ulp_error, Some(BigRational::new(BigInt::from(2), BigInt::from(5))));
+}
+
+#[test]
+fn binary128_extremes_reconstruct_exactly() {
+	let binary128 = FloatSpec {
+		name: "binary128",
+		exponent_bits: 15,
+		significand_bits: 112,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: 16383,
+		explicit_integer_bit: false,
 	};
-	let parsed = parse_decimal("-123.456").expect("parse decimal");
-	let soft = parsed_to_softfloat(&parsed, &spec, RoundingMode::HalfEven);
-	let bits = softfloat_to_bits(&soft, &spec);
+	let max_bits = format!("0{}0{}", "1".repeat(14), "1".repeat(112));
+	let max = bits_to_softfloat(&max_bits, &binary128).unwrap();
+	assert_eq!(max.class, Class::Normal);
+	let expected = (BigRational::from_integer(BigInt::from(2)) - BigRational::new(BigInt::one(), BigInt::one() << 112))
+		* BigRational::from_integer(BigInt::one() << 16383);
+	assert_eq!(softfloat_to_rational(&max, &binary128), Some(expected.clone()));
+	let back = parsed_to_softfloat(&ParsedValue::Finite(expected), &binary128, RoundingMode::HalfEven);
+	assert_eq!(softfloat_to_bits(&back, &binary128).unwrap(), max_bits);
+
+	let min_subnormal = bits_to_softfloat(&format!("{}1", "0".repeat(127)), &binary128).unwrap();
 	assert_eq!(
-		bits,
-		"1100000001011110110111010010111100011010100111111011111001110111"
+		softfloat_to_rational(&min_subnormal, &binary128),
+		Some(BigRational::new(BigInt::one(), BigInt::one() << 16494))
+	);
+}
+
+#[test]
+fn input_rational_is_printed_as_a_reduced_fraction() {
+	assert_eq!(format_fraction(&parsed_rational("0.1")), "1/10");
+	assert_eq!(format_fraction(&parsed_rational("-2.50")), "-5/2");
+	assert_eq!(format_fraction(&parsed_rational("3")), "3/1");
+}
+
+#[test]
+fn subtraction_reports_cancelled_bits() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let operand = |raw: &str| parsed_to_softfloat(&parse_decimal(raw).unwrap(), &spec, RoundingMode::HalfEven);
+	let cancel = subtraction_cancellation(&operand("1.0000001"), &operand("1"), &spec).unwrap();
+	assert_eq!(cancel.bits, 23);
+	assert_eq!(cancel.amplification, Some(BigRational::from_integer(BigInt::from(16_777_217))));
+
+	let cancel = subtraction_cancellation(&operand("1.5"), &operand("1.25"), &spec).unwrap();
+	assert_eq!(cancel.bits, 2);
+	assert_eq!(subtraction_cancellation(&operand("3"), &operand("-1"), &spec).unwrap().bits, 0);
+	let same = subtraction_cancellation(&operand("2"), &operand("2"), &spec).unwrap();
+	assert_eq!((same.bits, same.amplification), (24, None));
+	assert!(subtraction_cancellation(&operand("inf"), &operand("1"), &spec).is_none());
+}
+
+#[test]
+fn preimage_spans_the_midpoints_to_the_neighbours() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let pattern = |hex: &str| bits_to_softfloat(&hex_to_bits(hex, 16).unwrap(), &spec).unwrap();
+	let one = pattern("3C00");
+	let (lower, upper) = rounding_midpoints(&one, &spec).unwrap().unwrap();
+	let interval = preimage(&one, &spec, RoundingMode::HalfEven).unwrap().unwrap();
+	assert_eq!(interval.lo, Some(lower));
+	assert_eq!(interval.hi, Some(upper));
+	// 1.0 has an even significand, so both ties round to it.
+	assert!(interval.lo_closed && interval.hi_closed);
+	assert_eq!(
+		interval.decimals_with_digits(3),
+		Some((BigInt::from(1000), BigInt::from(1000)))
+	);
+
+	let odd = preimage(&pattern("3C01"), &spec, RoundingMode::HalfEven).unwrap().unwrap();
+	assert!(!odd.lo_closed && !odd.hi_closed);
+	let truncated = preimage(&one, &spec, RoundingMode::TowardZero).unwrap().unwrap();
+	assert_eq!(truncated.lo, Some(parsed_rational("1")));
+	assert_eq!(truncated.hi, Some(parsed_rational("1.0009765625")));
+	assert!(truncated.lo_closed && !truncated.hi_closed);
+	assert!(preimage(&pattern("7C00"), &spec, RoundingMode::HalfEven).unwrap().is_none());
+}
+
+#[test]
+fn plus_sign_marks_positive_values() {
+	let signed = DisplayOptions {
+		plus_sign: true,
+		..display(10, Notation::Plain)
+	};
+	assert_eq!(format_rational(&parsed_rational("1.5"), &signed), "+1.5");
+	assert_eq!(format_rational(&parsed_rational("-1.5"), &signed), "-1.5");
+	assert_eq!(format_rational(&BigRational::zero(), &signed), "+0");
+	assert_eq!(format_rational(&parsed_rational("1.5"), &display(10, Notation::Plain)), "1.5");
+	let scientific = DisplayOptions {
+		plus_sign: true,
+		..display(10, Notation::Scientific)
+	};
+	assert_eq!(format_rational(&parsed_rational("1500"), &scientific), "+1.5e+3");
+}
+
+#[test]
+fn underflow_boundary_is_one_subnormal_ulp_wide() {
+	let spec = preset_spec(FormatChoice::Fp16).unwrap();
+	let boundary = underflow_boundary(&spec).unwrap();
+	assert_eq!(boundary.min_normal, BigRational::new(BigInt::one(), BigInt::one() << 14));
+	assert_eq!(boundary.subnormal_ulp, BigRational::new(BigInt::one(), BigInt::one() << 24));
+	assert_eq!(&boundary.min_normal - &boundary.max_subnormal, boundary.subnormal_ulp);
+	let largest = bits_to_softfloat(&hex_to_bits("03FF", 16).unwrap(), &spec).unwrap();
+	assert_eq!(largest.class, Class::Subnormal);
+	assert_eq!(softfloat_to_rational(&largest, &spec), Some(boundary.max_subnormal));
+	assert!(underflow_boundary(&custom_spec(1, 2).unwrap()).is_none());
+}
+
+#[test]
+fn arithmetic_propagates_nan_payloads() {
+	let spec = preset_spec(FormatChoice::Fp32).unwrap();
+	let pattern = |hex: &str| bits_to_softfloat(&hex_to_bits(hex, 32).unwrap(), &spec).unwrap();
+	let one = pattern("3F800000");
+	let payload = |sf: &SoftFloat| softfloat_to_value(sf, &spec);
+	let quiet_five = ValueOrSpecial::Nan { quiet: true, payload: BigUint::from(5u32) };
+
+	let (sum, err) = two_sum_softfloats(&pattern("7FC00005"), &one, &spec, RoundingMode::HalfEven);
+	assert_eq!(payload(&sum), quiet_five);
+	assert!(err.is_none());
+	// A signaling NaN is quieted but keeps its payload.
+	let (product, _) = mul_softfloats(&one, &pattern("7F800005"), &spec, RoundingMode::HalfEven);
+	assert_eq!(payload(&product), quiet_five);
+	// With two NaN operands the first one wins.
+	let rem = remainder_softfloats(
+		&pattern("FFC00007"),
+		&pattern("7FC00005"),
+		&spec,
+		RoundingMode::HalfEven,
+		QuotientRounding::NearestEven,
 	);
+	assert_eq!(bits_to_hex(&softfloat_to_bits(&rem, &spec).unwrap()), "FFC00007");
+}
+
+#[test]
+fn posit_16_1_decomposes_into_regime_exponent_fraction() {
+	let fields = match decode_posit("0011010000000000", 1).unwrap() {
+		PositDecoding::Finite(fields) => fields,
+		other => panic!("expected a finite posit, got {other:?}"),
+	};
+	assert!(!fields.sign);
+	assert_eq!((fields.regime, fields.regime_bits), (-1, 2));
+	assert_eq!((fields.exponent, fields.exponent_bits), (1, 1));
+	assert_eq!((fields.fraction.clone(), fields.fraction_bits), (BigUint::from(0x400u32), 12));
+	assert_eq!(fields.value(), parsed_rational("0.625"));
+
+	for (bits, expected) in [
+		("0100000000000000", "1"),
+		("0101000000000000", "2"),
+		("0111000000000000", "16"),
+		("1100000000000000", "-1"),
+		("0000000000000001", "0.0000000037252902984619140625"),
+	] {
+		match decode_posit(bits, 1).unwrap() {
+			PositDecoding::Finite(fields) => assert_eq!(fields.value(), parsed_rational(expected), "{bits}"),
+			other => panic!("{bits}: expected a finite posit, got {other:?}"),
+		}
+	}
+	assert_eq!(decode_posit("0000000000000000", 1).unwrap(), PositDecoding::Zero);
+	assert_eq!(decode_posit("1000000000000000", 1).unwrap(), PositDecoding::NaR);
+	assert!(decode_posit("0102", 1).is_err());
+	// Wide patterns would push the scale past i32; they are rejected outright.
+	assert!(decode_posit(&format!("0{}", "1".repeat(MAX_POSIT_BITS)), 16).is_err());
+	match decode_posit(&format!("0{}", "1".repeat(MAX_POSIT_BITS - 1)), 16).unwrap() {
+		PositDecoding::Finite(fields) => {
+			assert_eq!(fields.regime, MAX_POSIT_BITS as i64 - 2);
+			assert_eq!(fields.value(), BigRational::from_integer(BigInt::one() << ((MAX_POSIT_BITS - 2) << 16)));
+		}
+		other => panic!("expected a finite posit, got {other:?}"),
+	}
 }
 
 #[derive(Deserialize)]
@@ -181,6 +2051,10 @@ fn spec_from_dump(dump: &ReferenceDump) -> FloatSpec {
 		name,
 		exponent_bits: dump.exponent_width,
 		significand_bits: dump.significand_width,
+		has_infinity: true,
+		nan_encoding: NanPolicy::AllOnesExponent,
+		bias: standard_bias(dump.exponent_width),
+		explicit_integer_bit: false,
 	}
 }
 
@@ -267,3 +2141,107 @@ fn site_reference_float4_e2m1_full_space() {
 	assert_eq!(dump.count, 16);
 	compare_against_reference(dump);
 }
+
+#[derive(Deserialize)]
+struct NumpySample {
+	hex: String,
+	sign: bool,
+	special: Option<String>,
+	num: Option<String>,
+	den: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NumpyDump {
+	samples: Vec<NumpySample>,
+}
+
+/// Runs scripts/numpy_reference.py when AFCVT_NUMPY_REFERENCE is set; `None`
+/// (skip) when the flag is unset or python3/numpy is unavailable.
+fn run_numpy_reference(format: &str) -> Option<NumpyDump> {
+	std::env::var_os("AFCVT_NUMPY_REFERENCE")?;
+	let output = match Command::new("python3")
+		.arg("scripts/numpy_reference.py")
+		.arg(format!("--format={format}"))
+		.output()
+	{
+		Ok(output) => output,
+		Err(err) => {
+			eprintln!("skipping numpy reference: python3 unavailable ({err})");
+			return None;
+		}
+	};
+	if output.status.code() == Some(3) {
+		eprintln!("skipping numpy reference: numpy unavailable");
+		return None;
+	}
+	if !output.status.success() {
+		panic!(
+			"numpy reference script failed: {}",
+			String::from_utf8_lossy(&output.stderr)
+		);
+	}
+	Some(serde_json::from_slice(&output.stdout).expect("parse numpy json"))
+}
+
+fn compare_against_numpy(choice: FormatChoice, format: &str) {
+	let Some(dump) = run_numpy_reference(format) else {
+		return;
+	};
+	let spec = preset_spec(choice).unwrap();
+	for sample in dump.samples {
+		let bits = hex_to_bits(&sample.hex, total_bits(&spec).unwrap()).unwrap();
+		let soft = bits_to_softfloat(&bits, &spec).expect("parse bits");
+		match sample.special.as_deref() {
+			Some("nan") => assert_eq!(soft.class, Class::Nan, "class mismatch for hex {}", sample.hex),
+			Some(_) => {
+				assert!(soft.class.is_infinite(), "class mismatch for hex {}", sample.hex);
+				assert_eq!(soft.sign, sample.sign, "sign mismatch for hex {}", sample.hex);
+			}
+			None => {
+				assert_eq!(soft.sign, sample.sign, "sign mismatch for hex {}", sample.hex);
+				let num = BigInt::from_str(sample.num.as_deref().expect("num")).unwrap();
+				let den = BigInt::from_str(sample.den.as_deref().expect("den")).unwrap();
+				assert_eq!(
+					softfloat_to_rational(&soft, &spec).expect("rational value"),
+					BigRational::new(num, den),
+					"value mismatch for hex {}",
+					sample.hex
+				);
+			}
+		}
+	}
+}
+
+#[test]
+fn numpy_reference_fp16_random_patterns() {
+	compare_against_numpy(FormatChoice::Fp16, "fp16");
+}
+
+#[test]
+fn numpy_reference_fp32_random_patterns() {
+	compare_against_numpy(FormatChoice::Fp32, "fp32");
+}
+
+/// Checks the library with only `alloc`, so a `std` import creeping into the
+/// core fails here rather than in an embedded build. Set AFCVT_NO_STD_TARGET
+/// (e.g. `thumbv7em-none-eabihf`) to check against a real bare-metal target.
+#[test]
+fn core_builds_without_std() {
+	let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+	let manifest_dir = env!("CARGO_MANIFEST_DIR");
+	let mut command = Command::new(cargo);
+	command
+		.current_dir(manifest_dir)
+		.args(["check", "--lib", "--no-default-features", "--quiet"])
+		.args(["--target-dir", "target/no-std"]);
+	if let Some(target) = std::env::var_os("AFCVT_NO_STD_TARGET") {
+		command.arg("--target").arg(target);
+	}
+	let output = command.output().expect("run cargo check");
+	assert!(
+		output.status.success(),
+		"no_std check failed: {}",
+		String::from_utf8_lossy(&output.stderr)
+	);
+}